@@ -0,0 +1,43 @@
+use crate::config::LangConfig;
+use crate::filesystemconfig::{self, FileSystemConfig};
+use crate::sqliteconfig::{self, SqliteConfig};
+use crate::tomlconfig::{TomlConfig, LANGUAGE_FILE_NAME};
+use std::path::Path;
+
+/// Names of the ways a language can be stored
+pub const BACKEND_NAMES: [&str; 3] = ["text", "toml", "sqlite"];
+
+/// Backend @name if set, otherwise the one whose files are in @dir. Text files are
+/// used when there are none, that is how languages were stored before backends existed
+pub fn resolve_name(dir: &Path, name: Option<&str>) -> &'static str {
+    match name {
+        Some("toml") => "toml",
+        Some("sqlite") => "sqlite",
+        Some(_) => "text",
+        None if dir.join(LANGUAGE_FILE_NAME).exists() => "toml",
+        None if dir.join(sqliteconfig::DATABASE_FILE_NAME).exists() => "sqlite",
+        None => "text",
+    }
+}
+
+/// Config in @dir stored with backend @name, see resolve_name. It is not loaded yet
+pub fn open(dir: &Path, name: Option<&str>) -> Box<dyn LangConfig> {
+    match resolve_name(dir, name) {
+        "toml" => Box::new(TomlConfig::in_dir(dir)),
+        "sqlite" => Box::new(SqliteConfig::in_dir(dir)),
+        _ => Box::new(FileSystemConfig::in_dir(dir)),
+    }
+}
+
+/// Checks whether @dir holds config of any backend
+pub fn has_config(dir: &Path) -> bool {
+    let toml_len = dir
+        .join(LANGUAGE_FILE_NAME)
+        .metadata()
+        .map(|m| m.len())
+        .unwrap_or(0);
+
+    toml_len > 0
+        || dir.join(sqliteconfig::DATABASE_FILE_NAME).exists()
+        || filesystemconfig::has_config(dir)
+}
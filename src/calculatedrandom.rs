@@ -1,85 +1,279 @@
 use crate::config::LangConfig;
-use crate::rangen::RandomEngine;
-use crate::{syllables, Result};
-use rand::rngs::ThreadRng;
+use crate::error::LangErr::InvalidSyllable;
+use crate::rangen::{EngineRng, RandomEngine};
+use crate::tuning::{Metric, Tuning};
+use crate::wanted::WordPosition;
+use crate::{proposals, rangen, syllables, Result};
 use rand::Rng;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
 
 pub struct CalculatedRandom {
-    occ_count: HashMap<String, u32>,
-    off_by_map: HashMap<String, f64>,
-    sum: u32,
-    rng: ThreadRng,
-    // Select from top % results
-    rng_range: f64,
+    // Syllables sorted so that every run visits them in the same order,
+    // the vectors below are indexed the same way
+    syllables: Vec<String>,
+    // Occurrences in the database and in words created so far, plus proposal history
+    occ_count: Vec<f64>,
+    wanted: Vec<f64>,
+    sum: f64,
+    // Same as above for every position of words which has wanted values of its own,
+    // None for positions which are balanced as part of the overall distribution
+    at_position: Vec<Option<PositionCounts>>,
+    rng: EngineRng,
+    tuning: Tuning,
+    // Words made by the last create_words call with indexes of their syllables, see discard
+    created: Vec<(String, Vec<usize>)>,
 }
 
+/// Occurrences and wanted values of syllables at a single position of words
+struct PositionCounts {
+    occ_count: Vec<f64>,
+    wanted: Vec<f64>,
+    sum: f64,
+}
+
+/// Syllable which may be pulled, ordered so that the worst one is the greatest
+struct Candidate {
+    score: f64,
+    index: usize,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Ties go to the syllable sorted last, so the order does not depend on the heap
+        self.score
+            .partial_cmp(&other.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
 impl CalculatedRandom {
-    fn pull_syllable(&mut self, pos: usize, len: usize, cfg: &dyn LangConfig) -> String {
-        self.off_by_map.clear();
-
-        //How would offby change if we pulled this syllable
-        for s_po in self.occ_count.keys() {
-            // List through syllable that may be pulled
-            let mut offby = 0.0; // How much values are off wanted values
-            for (s_o, count) in &self.occ_count {
-                // Compare syllable pulled s_po to others s_o
-                let mut cadj = *count;
-                if s_o == s_po {
-                    // If this syllable was pulled
-                    cadj += 1; // Add one
-                }
-                //Otherwise it stays the same
+    /// How the offby changes if syllable @i is pulled at @position. Only the distribution
+    /// of that position is compared if it has wanted values of its own
+    fn score(&self, i: usize, position: WordPosition) -> f64 {
+        match &self.at_position[position as usize] {
+            Some(at) => self.score_in(at.occ_count[i], at.sum, at.wanted[i]),
+            None => self.score_in(self.occ_count[i], self.sum, self.wanted[i]),
+        }
+    }
 
-                let adj_real = cadj as f64 / self.sum as f64; // Calculate frequency
+    /// How the offby of a distribution with @sum occurrences changes if a syllable which
+    /// occurred @count times and is @wanted is pulled. Every other syllable is off the same
+    /// way whichever one is pulled, so only the pulled one is compared
+    fn score_in(&self, count: f64, sum: f64, wanted: f64) -> f64 {
+        // Frequency before and after the pull, the sum grows by the pulled syllable too
+        let (before, after) = if self.tuning.metric == Metric::Kl {
+            // Smoothed, a syllable which did not occur yet would be infinitely off
+            let total = sum + 1.0 + self.syllables.len() as f64;
+            ((count + 1.0) / total, (count + 2.0) / total)
+        } else {
+            let total = sum + 1.0;
+            (count / total, (count + 1.0) / total)
+        };
 
-                offby += (*cfg.wanted().get(s_o).unwrap() - (adj_real)).abs(); // Calculate offby for this syllable
-            }
-            self.off_by_map.insert(s_po.clone(), offby); // Add this option to sorted map
+        self.tuning.metric.term(wanted, after) - self.tuning.metric.term(wanted, before)
+    }
+
+    /// Counts pulling (@change 1) or throwing away (@change -1) syllable @i at @position
+    fn count(&mut self, i: usize, position: WordPosition, change: f64) {
+        self.occ_count[i] += change;
+        self.sum += change;
+
+        if let Some(at) = &mut self.at_position[position as usize] {
+            at.occ_count[i] += change;
+            at.sum += change;
         }
+    }
 
-        // How many possibilities should we prepare for considering our rng range
-        let possible_count = ((self.occ_count.len() as f64) * self.rng_range) as usize;
+    /// Throws away syllables with @indexes pulled for a word of @len syllables
+    fn uncount(&mut self, indexes: &[usize], len: usize) {
+        for (pos, index) in indexes.iter().enumerate() {
+            self.count(*index, WordPosition::of(pos, len), -1.0);
+        }
+    }
+
+    fn pull_syllable(
+        &mut self,
+        previous: &[String],
+        len: usize,
+        cfg: &dyn LangConfig,
+    ) -> Result<usize> {
+        let position = WordPosition::of(previous.len(), len);
 
-        // Not every syllable can be used at this position
-        let mut possible_results = Vec::with_capacity(possible_count);
-        let mut possible_result_found = false;
+        // How many possibilities should we prepare for considering our top fraction
+        let possible_count =
+            (((self.syllables.len() as f64) * self.tuning.top_fraction) as usize).max(1);
 
-        for s in syllables::syllables_sorted_by_occurrence(&self.off_by_map) {
-            // Iterate the sorted map
-            if syllables::is_syllable_pos_valid(&s, pos, len, cfg) {
-                //Until you find a valid char
-                possible_results.push(s);
-                possible_result_found = true;
+        // Best valid syllables, the worst of them on top so it can be replaced
+        let mut best = BinaryHeap::with_capacity(possible_count + 1);
+        let mut rejected = Vec::new();
+        for (index, s) in self.syllables.iter().enumerate() {
+            // Not every syllable can be used at this position
+            if !syllables::is_syllable_valid_after(s, previous, len, cfg) {
+                rejected.push(s.clone());
+                continue;
             }
 
-            if possible_results.len() >= possible_count {
-                // We have enough results for our eng
-                break;
+            best.push(Candidate {
+                score: self.score(index, position),
+                index,
+            });
+            if best.len() > possible_count {
+                best.pop();
             }
         }
 
-        if !possible_result_found {
+        if best.is_empty() {
             // There are no valid results
-            panic!("Can't construct any new random words");
+            return Err(rangen::no_valid_syllable(previous.len(), len, rejected));
         }
 
-        // Possible results len used in case there were not enough results
-        let result = possible_results[self.rng.gen_range(0, possible_results.len())].clone();
+        let possible_results = best.into_sorted_vec(); // Best first
+        let chosen = match self.tuning.temperature {
+            Some(temperature) => self.softmax_index(&possible_results, temperature),
+            None => rangen::random_index(&mut self.rng, possible_results.len()),
+        };
+        let result = possible_results[chosen].index;
 
-        if result.as_str() == "" {
-            panic!("No suitable syllable could be pulled");
+        if self.syllables[result].is_empty() {
+            return Err(InvalidSyllable(String::new())); // Empty line in syllables
         }
 
         // Increase occurence of this syllable that we just pulled
-        self.occ_count
-            .entry(result.to_string())
-            .and_modify(|e| *e += 1);
+        self.count(result, position, 1.0);
+
+        Ok(result)
+    }
+
+    /// Picks one of @candidates, the closer its score is to the best one the likelier it is.
+    /// Higher @temperature evens the chances out
+    fn softmax_index(&mut self, candidates: &[Candidate], temperature: f64) -> usize {
+        let best = candidates[0].score;
+        let weights: Vec<f64> = candidates
+            .iter()
+            .map(|c| (-(c.score - best) / temperature).exp())
+            .collect();
 
-        // Increase total sum
-        self.sum += 1;
+        let mut pick = self.rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for (i, weight) in weights.iter().enumerate() {
+            if pick < *weight {
+                return i;
+            }
+            pick -= weight;
+        }
+
+        candidates.len() - 1 // Rounding may leave a tiny bit of pick
+    }
+
+    /// Engine with @tuning instead of the one of @cfg. Syllables weighted in @history
+    /// count as if they occurred that many times more, overall and at positions with wanted
+    /// values of their own. The history is scaled down to a small share of the database first
+    pub fn with_tuning(
+        cfg: &dyn LangConfig,
+        tuning: Tuning,
+        history: &HashMap<String, f64>,
+        seed: Option<u64>,
+    ) -> Result<Self> {
+        let counts = syllables::db_syllable_occurrences_as_count(cfg)?;
+        let mut syllables: Vec<String> = counts.keys().cloned().collect();
+        syllables.sort();
+
+        // Some history is kept even when the database is nearly empty
+        let occurrences = (counts.values().sum::<u32>() as f64).max(syllables.len() as f64);
+        let overall_history = proposals::scaled(history, occurrences);
+
+        let occ_count: Vec<f64> = syllables
+            .iter()
+            .map(|s| counts[s] as f64 + overall_history.get(s).cloned().unwrap_or(0.0))
+            .collect();
+        let wanted = syllables
+            .iter()
+            .map(|s| cfg.wanted().get(s).map(|w| w.overall).unwrap_or(0.0))
+            .collect();
+
+        let counts_at = syllables::db_syllable_occurrences_by_position(cfg)?;
+        let at_position = WordPosition::ALL
+            .iter()
+            .map(|position| {
+                let has_own = cfg
+                    .wanted()
+                    .values()
+                    .any(|w| w.only_at(*position).is_some());
+                if !has_own {
+                    return None;
+                }
+
+                // Proposals don't know their position, they count at every position
+                // scaled to the occurrences there
+                let counts = &counts_at[*position as usize];
+                let occurrences = (counts.values().sum::<u32>() as f64).max(syllables.len() as f64);
+                let history_at = proposals::scaled(history, occurrences);
+                let occ_count: Vec<f64> = syllables
+                    .iter()
+                    .map(|s| {
+                        counts.get(s).cloned().unwrap_or(0) as f64
+                            + history_at.get(s).cloned().unwrap_or(0.0)
+                    })
+                    .collect();
+                Some(PositionCounts {
+                    sum: occ_count.iter().sum(),
+                    occ_count,
+                    wanted: syllables
+                        .iter()
+                        .map(|s| cfg.wanted().get(s).map(|w| w.at(*position)).unwrap_or(0.0))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        Ok(CalculatedRandom {
+            sum: occ_count.iter().sum(),
+            syllables,
+            occ_count,
+            wanted,
+            at_position,
+            rng: rangen::engine_rng(seed),
+            tuning,
+            created: Vec::new(),
+        })
+    }
+
+    fn create_word(&mut self, len: usize, cfg: &dyn LangConfig) -> Result<String> {
+        let mut indexes = Vec::with_capacity(len);
+        let mut syllables = Vec::with_capacity(len);
+
+        for _pos in 0..len {
+            match self.pull_syllable(&syllables, len, cfg) {
+                Ok(index) => {
+                    indexes.push(index);
+                    syllables.push(self.syllables[index].clone());
+                }
+                Err(e) => {
+                    // Word is thrown away, so are its syllables
+                    self.uncount(&indexes, len);
+                    return Err(e);
+                }
+            }
+        }
 
-        result
+        let word = syllables.concat();
+        self.created.push((word.clone(), indexes));
+        Ok(word)
     }
 }
 
@@ -90,33 +284,28 @@ impl RandomEngine for CalculatedRandom {
         max_len: u32,
         count: u32,
         cfg: &dyn LangConfig,
-    ) -> Vec<String> {
+    ) -> Result<Vec<String>> {
         let mut result = Vec::with_capacity(count as usize);
+        self.created.clear();
 
-        let mut rng = self.rng;
         for _nth_word in 0..count {
-            let chosen_length = rng.gen_range(min_len, max_len + 1);
-            let mut word = String::new();
-
-            for pos in 0..chosen_length {
-                word.push_str(&self.pull_syllable(pos as usize, chosen_length as usize, cfg));
-            }
+            let chosen_length = self.rng.gen_range(min_len, max_len + 1) as usize;
+            let word = rangen::retry_word(|| self.create_word(chosen_length, cfg))?;
 
             result.push(word);
         }
 
-        result
+        Ok(result)
     }
 
-    fn with_config(cfg: &dyn LangConfig) -> Result<Self> {
-        let occ_count = syllables::db_syllable_occurrences_as_count(cfg)?;
-        let sum = occ_count.values().sum(); // Must be done here because occ_count is given to CalculatedRandom after
-        Ok(CalculatedRandom {
-            occ_count,
-            off_by_map: HashMap::new(),
-            sum,
-            rng: rand::thread_rng(),
-            rng_range: 0.15,
-        })
+    fn discard(&mut self, word: &str) {
+        if let Some(i) = self.created.iter().position(|(w, _)| w == word) {
+            let (_, indexes) = self.created.remove(i);
+            self.uncount(&indexes, indexes.len());
+        }
+    }
+
+    fn with_config(cfg: &dyn LangConfig, seed: Option<u64>) -> Result<Self> {
+        CalculatedRandom::with_tuning(cfg, cfg.tuning().clone(), &HashMap::new(), seed)
     }
 }
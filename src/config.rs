@@ -1,6 +1,14 @@
 use std::collections::HashMap;
+use std::path::Path;
 
+use crate::languagepack::LanguageInfo;
+use crate::lexicon::LexiconEntry;
+use crate::lint::LintIssue;
+use crate::phonology::Phonology;
+use crate::phonotactics::AdjacencyRule;
 use crate::syllables::SyllablePosition;
+use crate::tuning::Tuning;
+use crate::wanted::Wanted;
 use crate::Result;
 
 pub trait LangConfig {
@@ -13,14 +21,41 @@ pub trait LangConfig {
     fn romanization(&self) -> &HashMap<String, String>;
     fn set_romanization(&mut self, utf_to_ascii: HashMap<String, String>);
 
-    fn wanted(&self) -> &HashMap<String, f64>;
-    fn set_wanted(&mut self, wanted: HashMap<String, f64>);
+    fn wanted(&self) -> &HashMap<String, Wanted>;
+    fn set_wanted(&mut self, wanted: HashMap<String, Wanted>);
 
-    fn database(&self) -> &Vec<String>;
-    fn set_database(&mut self, db: Vec<String>);
-    fn append_database(&mut self, words: &[String]);
-    fn delete_from_database(&mut self, word: &str) -> bool;
+    fn phonology(&self) -> &Phonology;
+    fn set_phonology(&mut self, phonology: Phonology);
+
+    fn adjacency_rules(&self) -> &Vec<AdjacencyRule>;
+    fn set_adjacency_rules(&mut self, rules: Vec<AdjacencyRule>);
+
+    /// Defaults of CalculatedRandom, gen arguments override them
+    fn tuning(&self) -> &Tuning;
+    fn set_tuning(&mut self, tuning: Tuning);
+
+    /// Every entry of the database. Backends which read words only when they are
+    /// asked for report errors of reading here
+    fn database(&self) -> Result<&Vec<LexiconEntry>>;
+    fn set_database(&mut self, db: Vec<LexiconEntry>);
+    fn append_database(&mut self, entries: &[LexiconEntry]);
+    /// Deletes the first entry of @word and returns it, None if there is no such entry
+    fn delete_from_database(&mut self, word: &str) -> Result<Option<LexiconEntry>>;
+    /// Deletes the first entry equal to @entry, false if there is no such entry
+    fn delete_entry_from_database(&mut self, entry: &LexiconEntry) -> Result<bool>;
+
+    /// Name and description stored with the language, None if the backend does not store them
+    fn info(&self) -> Option<&LanguageInfo> {
+        None
+    }
+
+    /// Directory the language is stored in
+    fn dir(&self) -> &Path;
 
     fn load(&mut self) -> Result<()>;
+    /// Deletes stored config, other files in the directory are kept
+    fn remove(&mut self) -> Result<()>;
+    /// Checks stored config for inconsistencies, config does not have to be loaded
+    fn lint(&self) -> Result<Vec<LintIssue>>;
     fn flush(&mut self) -> Result<()>;
 }
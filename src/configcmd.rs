@@ -1,8 +1,12 @@
 use crate::config::LangConfig;
-use crate::syllables;
+use crate::error::LangErr::InvalidTuning;
+use crate::syllables::{self, SyllablePosition};
+use crate::tuning;
+use crate::wanted::{Wanted, WordPosition};
 use crate::Result;
-use crate::TakeAppArg;
+use crate::{ConfigUse, TakeAppArg};
 use clap::ArgMatches;
+use std::collections::HashMap;
 
 pub struct ConfigCmd;
 
@@ -15,10 +19,25 @@ impl ConfigCmd {
 }
 
 fn wanted(_matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
-    let list = syllables::syllables_by_occurrence_desc(cfg.wanted());
+    let overall: HashMap<String, f64> = cfg
+        .wanted()
+        .iter()
+        .map(|(s, w)| (s.clone(), w.overall))
+        .collect();
+    let list = syllables::syllables_by_occurrence_desc(&overall);
 
     for (syllable, percentage) in list {
-        println!("{}: {:.2}%", &syllable, &percentage * 100.0);
+        // Only positions with values of their own are shown
+        let positional: Vec<String> = WordPosition::ALL
+            .iter()
+            .filter_map(|p| {
+                cfg.wanted()[&syllable]
+                    .only_at(*p)
+                    .map(|v| format!("{} {:.2}%", p, v * 100.0))
+            })
+            .collect();
+
+        print_share(&syllable, percentage, &positional);
     }
 
     Ok(())
@@ -27,13 +46,172 @@ fn wanted(_matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
 fn real(_matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
     let list =
         syllables::syllables_by_occurrence_desc(&syllables::db_syllable_occurrences_as_percentage(
-            &syllables::db_syllable_occurrences_as_count(cfg).unwrap(),
+            &syllables::db_syllable_occurrences_as_count(cfg)?,
         ));
 
+    // Positions no word has a syllable at are left out
+    let by_position: Vec<(WordPosition, HashMap<String, f64>)> = WordPosition::ALL
+        .iter()
+        .zip(syllables::db_syllable_occurrences_by_position(cfg)?)
+        .filter(|(_, counts)| counts.values().any(|c| *c > 0))
+        .map(|(p, counts)| {
+            (
+                *p,
+                syllables::db_syllable_occurrences_as_percentage(&counts),
+            )
+        })
+        .collect();
+
     for (syllable, percentage) in list {
-        println!("{}: {:.2}%", &syllable, &percentage * 100.0);
+        let positional: Vec<String> = by_position
+            .iter()
+            .map(|(p, percentages)| {
+                let share = percentages.get(&syllable).cloned().unwrap_or(0.0);
+                format!("{} {:.2}%", p, share * 100.0)
+            })
+            .collect();
+
+        print_share(&syllable, percentage, &positional);
+    }
+
+    Ok(())
+}
+
+/// Prints share of @syllable among all syllables, followed by its @positional shares if any
+fn print_share(syllable: &str, share: f64, positional: &[String]) {
+    if positional.is_empty() {
+        println!("{}: {:.2}%", syllable, share * 100.0);
+    } else {
+        println!(
+            "{}: {:.2}% ({})",
+            syllable,
+            share * 100.0,
+            positional.join(", ")
+        );
+    }
+}
+
+/// Replaces syllables of @cfg with @generated. New syllables may be anywhere, are written
+/// the same way and get an equal share of wanted values, entries of removed syllables are
+/// dropped. Wanted values are scaled to add up to 1 again
+fn set_generated_syllables(cfg: &mut dyn LangConfig, generated: Vec<String>) {
+    let mut positions = HashMap::new();
+    let mut utf8_forms = HashMap::new();
+    let mut wanted = HashMap::new();
+    let share = 1.0 / generated.len() as f64;
+
+    for s in &generated {
+        let pos = cfg
+            .syllable_pos()
+            .get(s)
+            .cloned()
+            .unwrap_or(SyllablePosition::Any);
+        let utf8 = cfg
+            .romanization()
+            .get(s)
+            .cloned()
+            .unwrap_or_else(|| s.clone());
+        let value = cfg
+            .wanted()
+            .get(s)
+            .cloned()
+            .unwrap_or_else(|| Wanted::overall(share));
+
+        positions.insert(s.clone(), pos);
+        utf8_forms.insert(s.clone(), utf8);
+        wanted.insert(s.clone(), value);
+    }
+
+    let overall: f64 = wanted.values().map(|w| w.overall).sum();
+    if overall > 0.0 {
+        for w in wanted.values_mut() {
+            w.overall /= overall;
+        }
+    }
+    for position in WordPosition::ALL.iter() {
+        if wanted.values().all(|w| w.only_at(*position).is_none()) {
+            continue;
+        }
+
+        // Every syllable gets a value of its own here, so the values can be scaled together
+        let sum: f64 = wanted.values().map(|w| w.at(*position)).sum();
+        for w in wanted.values_mut() {
+            let value = if sum > 0.0 {
+                w.at(*position) / sum
+            } else {
+                0.0
+            };
+            w.set_at(*position, Some(value));
+        }
+    }
+
+    cfg.set_syllables(generated);
+    cfg.set_syllable_pos(positions);
+    cfg.set_romanization(utf8_forms);
+    cfg.set_wanted(wanted);
+}
+
+fn syllables(matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
+    if matches.is_present("generate") {
+        let generated = cfg.phonology().generate_syllables()?;
+
+        let added = generated
+            .iter()
+            .filter(|s| !cfg.syllables().contains(s))
+            .count();
+        let removed = cfg
+            .syllables()
+            .iter()
+            .filter(|s| !generated.contains(s))
+            .count();
+
+        set_generated_syllables(cfg, generated);
+        cfg.flush()?;
+
+        println!(
+            "Generated {} syllables, {} new and {} removed",
+            cfg.syllables().len(),
+            added,
+            removed
+        );
+    } else {
+        println!("{}", cfg.syllables().join("\n"));
+    }
+
+    Ok(())
+}
+
+/// Sets tuning values given in @matches, then shows the whole tuning
+fn tuning(matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
+    let mut tuning = cfg.tuning().clone();
+
+    for (arg, key) in &tuning::TUNING_ARGS {
+        if let Some(value) = matches.value_of(arg) {
+            tuning.set(key, value).map_err(InvalidTuning)?;
+        }
+    }
+
+    if tuning != *cfg.tuning() {
+        cfg.set_tuning(tuning);
+        cfg.flush()?;
+    }
+
+    for (key, value) in cfg.tuning().pairs() {
+        println!("{}: {}", key, value);
+    }
+
+    Ok(())
+}
+
+fn lint(_matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
+    let issues = cfg.lint()?;
+
+    for issue in &issues {
+        println!("{}", issue);
     }
 
+    println!("{} problems found", issues.len());
+
     Ok(())
 }
 
@@ -42,6 +220,24 @@ impl TakeAppArg for ConfigCmd {
         SUBCOMMAND
     }
 
+    fn config_use(&self, arguments: &ArgMatches) -> ConfigUse {
+        let changes_config = match arguments.subcommand() {
+            ("syllables", Some(matches)) => matches.is_present("generate"),
+            ("tuning", Some(matches)) => tuning::TUNING_ARGS
+                .iter()
+                .any(|(arg, _)| matches.is_present(arg)),
+            _ => false,
+        };
+
+        if arguments.subcommand_matches("lint").is_some() {
+            ConfigUse::Unloaded // Broken config would fail to load
+        } else if changes_config {
+            ConfigUse::LoadLocked
+        } else {
+            ConfigUse::Load
+        }
+    }
+
     fn do_exec(&mut self, arguments: &ArgMatches, mut cfg: Box<dyn LangConfig>) -> Result<()> {
         if arguments.is_present("wanted") {
             wanted(arguments, cfg.as_mut())?;
@@ -49,6 +245,15 @@ impl TakeAppArg for ConfigCmd {
         if arguments.is_present("real") {
             real(arguments, cfg.as_mut())?;
         }
+        if let Some(matches) = arguments.subcommand_matches("lint") {
+            return lint(matches, cfg.as_ref());
+        }
+        if let Some(matches) = arguments.subcommand_matches("tuning") {
+            tuning(matches, cfg.as_mut())?;
+        }
+        if let Some(matches) = arguments.subcommand_matches("syllables") {
+            syllables(matches, cfg.as_mut())?;
+        }
         Ok(())
     }
 }
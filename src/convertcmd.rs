@@ -0,0 +1,88 @@
+use crate::backend;
+use crate::config::LangConfig;
+use crate::error::LangErr::{ConfigExists, UnlistedEntries};
+use crate::filesafety::DirLock;
+use crate::journal::{self, JournalEntry};
+use crate::languagepack::LanguagePack;
+use crate::proposals;
+use crate::Result;
+use crate::{ConfigUse, TakeAppArg};
+use clap::ArgMatches;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct ConvertCmd;
+
+const SUBCOMMAND: &str = "convert";
+
+impl ConvertCmd {
+    pub fn new() -> ConvertCmd {
+        ConvertCmd
+    }
+}
+
+impl TakeAppArg for ConvertCmd {
+    fn subcommand(&self) -> &str {
+        SUBCOMMAND
+    }
+
+    fn config_use(&self, _arguments: &ArgMatches) -> ConfigUse {
+        ConfigUse::LoadLocked
+    }
+
+    fn do_exec(&mut self, arguments: &ArgMatches, mut cfg: Box<dyn LangConfig>) -> Result<()> {
+        let from = backend::resolve_name(cfg.dir(), arguments.value_of("backend"));
+        let to = arguments.value_of("to").unwrap();
+        let dir = match arguments.value_of("output") {
+            Some(output) => PathBuf::from(output),
+            None => cfg.dir().to_path_buf(),
+        };
+        let same_dir = dir == cfg.dir();
+
+        if same_dir && from == to {
+            eprintln!("Language is already stored as {}", to);
+            return Ok(());
+        }
+        if !same_dir && !arguments.is_present("force") && backend::has_config(&dir) {
+            return Err(ConfigExists(dir.display().to_string()));
+        }
+
+        // Source files may be removed below, nothing may be lost on the way
+        let left_out = LanguagePack::left_out(cfg.as_ref());
+        if !left_out.is_empty() {
+            return Err(UnlistedEntries(left_out));
+        }
+
+        // Language in another directory is locked too while it is written
+        let _target_lock = if same_dir {
+            None
+        } else {
+            fs::create_dir_all(&dir)?;
+            Some(DirLock::acquire(&dir)?)
+        };
+
+        let mut target = backend::open(&dir, Some(to));
+        LanguagePack::from_config(cfg.as_ref())?.apply_to(target.as_mut());
+        if same_dir {
+            target.flush()?;
+        } else {
+            // Words already in the other directory are replaced, which can't be undone
+            journal::record(&dir, &JournalEntry::replacement(), || target.flush())?;
+            proposals::reset(&dir)?; // History of the replaced language, this one's stays in the source
+        }
+
+        // Files of both backends would make it unclear which one is used
+        if same_dir {
+            cfg.remove()?;
+        }
+
+        println!(
+            "Language was converted from {} to {} in {}",
+            from,
+            to,
+            dir.display()
+        );
+
+        Ok(())
+    }
+}
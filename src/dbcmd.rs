@@ -1,49 +1,67 @@
 use crate::config::LangConfig;
+use crate::error::LangErr;
+use crate::journal::{self, JournalEntry};
+use crate::lexicon::{self, LexiconEntry};
+use crate::syllables;
 use crate::Result;
-use crate::TakeAppArg;
+use crate::{ConfigUse, TakeAppArg};
 use clap::ArgMatches;
 
 pub struct DatabaseCmd;
 
 const SUBCOMMAND: &str = "db";
 
+/// Most splits segment shows of a single word, long words may be split in very many ways
+const MAX_SHOWN_SPLITS: usize = 20;
+
 impl DatabaseCmd {
     pub fn new() -> DatabaseCmd {
         DatabaseCmd
     }
 }
 
-fn add(matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
-    let words = matches.values_of("add").unwrap();
-
-    let mut wstr = Vec::with_capacity(words.len());
+/// Adds @words, metadata for them is taken from @matches
+fn add(words: Vec<&str>, matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
+    let mut entries = Vec::with_capacity(words.len());
 
     for word in words {
-        wstr.push(word.to_string());
+        if !matches.is_present("force") {
+            syllables::is_word_valid(word, cfg)?;
+        }
+
+        let mut entry = LexiconEntry::created_today(word);
+        entry.gloss = matches.value_of("gloss").map(lexicon::clean_field);
+        entry.part_of_speech = matches.value_of("pos").map(lexicon::clean_field);
+        entry.tags = tags_of(matches);
+        entry.notes = matches.value_of("notes").map(lexicon::clean_field);
+        entries.push(entry);
     }
 
-    cfg.append_database(&wstr);
+    cfg.append_database(&entries);
 
-    if wstr.len() > 1 {
+    if entries.len() > 1 {
         println!("Words were added to the database");
     } else {
         println!("Word was added to the database");
     }
 
-    cfg.flush()
+    let dir = cfg.dir().to_path_buf();
+    journal::record(&dir, &JournalEntry::now(entries, Vec::new()), || {
+        cfg.flush()
+    })
 }
 
-fn del(matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
-    let words = matches.values_of("del").unwrap();
-
-    let mut successes = 0;
+fn del(words: Vec<&str>, cfg: &mut dyn LangConfig) -> Result<()> {
     let words_len = words.len(); // Can't iter words without moving
+    let mut removed = Vec::new();
 
     for word in words {
-        if cfg.delete_from_database(word) {
-            successes += 1;
+        // Kept whole, so it can be brought back
+        if let Some(entry) = cfg.delete_from_database(word)? {
+            removed.push(entry);
         }
     }
+    let successes = removed.len();
 
     if successes == words_len {
         println!("Words were deleted from the database");
@@ -53,26 +71,315 @@ fn del(matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
         println!("Some words could not be found and deleted from the database");
     }
 
-    cfg.flush()?;
+    let dir = cfg.dir().to_path_buf();
+    journal::record(&dir, &JournalEntry::now(Vec::new(), removed), || {
+        cfg.flush()
+    })
+}
+
+/// Lists changes of the database, oldest first
+fn history(matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
+    let entries = journal::read(cfg.dir())?;
+    let undone = journal::undone(&entries);
+
+    let count = match matches.value_of("count") {
+        Some(count) => count.parse()?,
+        None => entries.len(),
+    };
+    let skipped = entries.len().saturating_sub(count);
+
+    for (i, entry) in entries.iter().enumerate().skip(skipped) {
+        let number = i + 1;
+        let mut changes = Vec::new();
+        if !entry.added.is_empty() {
+            changes.push(format!("added {}", words_of(&entry.added)));
+        }
+        if !entry.removed.is_empty() {
+            changes.push(format!("removed {}", words_of(&entry.removed)));
+        }
+        if entry.replaced {
+            changes.push("replaced all words".to_string());
+        }
+
+        let mark = if undone.contains(&number) {
+            " (undone)"
+        } else {
+            ""
+        };
+        println!(
+            "{}. {} `{}`: {}{}",
+            number,
+            entry.time,
+            entry.command,
+            changes.join(", "),
+            mark
+        );
+    }
+
+    Ok(())
+}
+
+/// Reverts the last @n changes which were not undone yet, undoing is recorded as a change too
+fn undo(matches: &ArgMatches, cfg: &mut dyn LangConfig) -> Result<()> {
+    let n: usize = matches.value_of("n").unwrap().parse()?;
+
+    let entries = journal::read(cfg.dir())?;
+    let undone = journal::undone(&entries);
+    let replacement = journal::last_replacement(&entries);
+
+    // Undoing is undone by undoing the change again, not by undoing the undo
+    let to_undo: Vec<usize> = (replacement + 1..=entries.len())
+        .rev()
+        .filter(|number| !undone.contains(number) && entries[number - 1].undoes.is_empty())
+        .take(n)
+        .collect();
+
+    if to_undo.is_empty() && replacement > 0 {
+        println!(
+            "There is nothing to undo, change {} `{}` replaced all words and can't be undone",
+            replacement,
+            entries[replacement - 1].command
+        );
+        return Ok(());
+    } else if to_undo.is_empty() {
+        println!("There is nothing to undo");
+        return Ok(());
+    }
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+
+    for number in &to_undo {
+        let entry = &entries[number - 1];
+
+        // Entries are matched whole, other entries of the same word are kept
+        for e in entry.added.iter().rev() {
+            if cfg.delete_entry_from_database(e)? {
+                removed.push(e.clone());
+            }
+        }
+        cfg.append_database(&entry.removed);
+        added.extend(entry.removed.iter().cloned());
+    }
+
+    let mut record = JournalEntry::now(added, removed);
+    record.undoes = to_undo.clone();
+    let dir = cfg.dir().to_path_buf();
+    journal::record(&dir, &record, || cfg.flush())?;
+
+    let numbers: Vec<String> = to_undo.iter().map(usize::to_string).collect();
+    if numbers.len() > 1 {
+        println!("Changes {} were undone", numbers.join(", "));
+    } else {
+        println!("Change {} was undone", numbers.join(", "));
+    }
+
+    Ok(())
+}
+
+fn words_of(entries: &[LexiconEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| e.word.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Lists entries matching every filter set in @matches
+fn list(matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
+    let gloss = matches.value_of("gloss").map(str::to_lowercase);
+    let pos = matches.value_of("pos");
+    let tags = tags_of(matches);
+
+    cfg.database()?
+        .iter()
+        .filter(|e| match (&gloss, &e.gloss) {
+            (Some(wanted), Some(gloss)) => gloss.to_lowercase().contains(wanted),
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .filter(|e| match (pos, &e.part_of_speech) {
+            (Some(wanted), Some(pos)) => pos.eq_ignore_ascii_case(wanted),
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .filter(|e| tags.iter().all(|t| e.has_tag(t)))
+        .for_each(|e| println!("{}", e));
+
+    Ok(())
+}
+
+/// Tags given with --tag, a single value may hold more tags separated by commas
+fn tags_of(matches: &ArgMatches) -> Vec<String> {
+    match matches.values_of("tag") {
+        Some(values) => values
+            .flat_map(|v| v.split(','))
+            .map(lexicon::clean_field)
+            .filter(|t| !t.is_empty())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn segment(matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
+    if let Some(words) = matches.values_of("words") {
+        for word in words {
+            if let Some((splits, count)) = splits_or_report(word, cfg)? {
+                print_splits(word, &splits, count);
+            }
+        }
+    } else {
+        // Whole database, only ambiguous words are interesting
+        let mut ambiguous = 0;
+        let mut unsplittable = 0;
+
+        for entry in cfg.database()? {
+            match splits_or_report(&entry.word, cfg)? {
+                Some((splits, count)) if count > 1 => {
+                    print_splits(&entry.word, &splits, count);
+                    ambiguous += 1;
+                }
+                Some(_) => {}
+                None => unsplittable += 1,
+            }
+        }
+
+        println!("{} ambiguous words found", ambiguous);
+        if unsplittable > 0 {
+            println!("{} words can't be split", unsplittable);
+        }
+    }
+
+    Ok(())
+}
+
+fn check(_matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
+    let mut invalid = 0;
+
+    for entry in cfg.database()? {
+        match syllables::is_word_valid(&entry.word, cfg) {
+            Ok(()) => {}
+            Err(LangErr::InvalidWord(_, reason)) => {
+                println!("{}: {}", entry.word, reason);
+                invalid += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    println!("{} invalid words found", invalid);
+
     Ok(())
 }
 
-fn list(_matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
-    println!("{}", cfg.database().join("\n"));
+/// Lists words in the database at most --max syllable edits away from the given word,
+/// closest first
+fn similar(matches: &ArgMatches, cfg: &dyn LangConfig) -> Result<()> {
+    let word = matches.value_of("word").unwrap();
+    let max: usize = matches.value_of("max").unwrap().parse()?;
+    let split = syllables::split_into_syllables(word, cfg)?;
+
+    let mut found: Vec<(usize, &str)> = cfg
+        .database()?
+        .iter()
+        .filter(|e| e.word != word)
+        .filter_map(|e| {
+            let other = syllables::split_into_syllables(&e.word, cfg).ok()?;
+            let distance = syllables::edit_distance(&split, &other);
+            if distance <= max {
+                Some((distance, e.word.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    found.sort();
+
+    for (distance, similar) in &found {
+        println!("{} ({})", similar, distance);
+    }
+    println!("{} similar words found", found.len());
 
     Ok(())
 }
 
+/// (Splits shown, count of all splits) of @word, None after printing why there is none
+fn splits_or_report(word: &str, cfg: &dyn LangConfig) -> Result<Option<(Vec<Vec<String>>, u128)>> {
+    let split = syllables::all_splits(word, MAX_SHOWN_SPLITS, cfg)
+        .and_then(|splits| Ok((splits, syllables::count_splits(word, cfg)?)));
+
+    match split {
+        Ok(split) => Ok(Some(split)),
+        Err(LangErr::InvalidSyllable(part)) => {
+            println!("{}: {} can't be split into syllables", word, part);
+            Ok(None)
+        }
+        Err(LangErr::InvalidWord(_, reason)) => {
+            println!("{}: {}", word, reason);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Prints @splits of @word, telling how many of all @count splits are left out
+fn print_splits(word: &str, splits: &[Vec<String>], count: u128) {
+    let mut joined: Vec<String> = splits.iter().map(|split| split.join("-")).collect();
+    if count > splits.len() as u128 {
+        joined.push(format!("{} more", count - splits.len() as u128));
+    }
+
+    if count > 1 {
+        println!("{}: {} (ambiguous)", word, joined.join(" | "));
+    } else {
+        println!("{}: {}", word, joined.join(" | "));
+    }
+}
+
 impl TakeAppArg for DatabaseCmd {
     fn subcommand(&self) -> &str {
         SUBCOMMAND
     }
 
+    fn config_use(&self, arguments: &ArgMatches) -> ConfigUse {
+        let changes_words = match arguments.subcommand_name() {
+            Some(name) => name == "add" || name == "del" || name == "undo",
+            None => arguments.is_present("add") || arguments.is_present("del"),
+        };
+
+        if changes_words {
+            ConfigUse::LoadLocked
+        } else {
+            ConfigUse::Load
+        }
+    }
+
     fn do_exec(&mut self, arguments: &ArgMatches, mut cfg: Box<dyn LangConfig>) -> Result<()> {
+        match arguments.subcommand() {
+            ("add", Some(matches)) => {
+                let words = matches.values_of("words").unwrap().collect();
+                return add(words, matches, cfg.as_mut());
+            }
+            ("del", Some(matches)) => {
+                return del(matches.values_of("words").unwrap().collect(), cfg.as_mut());
+            }
+            ("list", Some(matches)) => return list(matches, cfg.as_ref()),
+            ("segment", Some(matches)) => return segment(matches, cfg.as_ref()),
+            ("check", Some(matches)) => return check(matches, cfg.as_ref()),
+            ("similar", Some(matches)) => return similar(matches, cfg.as_ref()),
+            ("history", Some(matches)) => return history(matches, cfg.as_ref()),
+            ("undo", Some(matches)) => return undo(matches, cfg.as_mut()),
+            _ => {}
+        }
+
         if arguments.is_present("add") {
-            add(arguments, cfg.as_mut())?;
+            add(
+                arguments.values_of("add").unwrap().collect(),
+                arguments,
+                cfg.as_mut(),
+            )?;
         } else if arguments.is_present("del") {
-            del(arguments, cfg.as_mut())?;
+            del(arguments.values_of("del").unwrap().collect(), cfg.as_mut())?;
         } else if arguments.is_present("list") {
             list(arguments, cfg.as_mut())?;
         } else {
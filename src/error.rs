@@ -3,10 +3,51 @@ type OsPath = String;
 #[derive(Debug)]
 pub enum LangErr {
     FileEmpty(OsPath),
+    MissingFile(OsPath),
     InvalidSyllable(String),
     InvalidSyllablePosition(String, usize),
+    /// Word and why it is not valid
+    InvalidWord(String, String),
+    /// Template and what is wrong with it
+    InvalidTemplate(String, String),
+    InvalidLanguageName(String),
+    LanguageExists(String),
+    UnknownLanguage(String),
+    /// Directory which already holds config of a language
+    ConfigExists(OsPath),
     Io(std::io::Error),
+    /// No syllable may be placed at @pos of a word with @len syllables, @rejected were
+    /// tried there and broke position or adjacency rules
+    NoValidSyllable {
+        pos: usize,
+        len: usize,
+        rejected: Vec<String>,
+    },
     ParseIntError(std::num::ParseIntError),
+    Sqlite(rusqlite::Error),
+    /// File was changed by someone else since it was loaded
+    ConfigChanged(OsPath),
+    /// Only @found of @wanted distinct words with @min_len to @max_len syllables could be made
+    WordSpaceExhausted {
+        wanted: usize,
+        found: usize,
+        min_len: u32,
+        max_len: u32,
+    },
+    /// Engine which does not read the proposal history it was asked to keep
+    HistoryUnused(String),
+    /// What is wrong with a tuning value
+    InvalidTuning(String),
+    /// Entries of syllables which are not listed, they can't be stored in a LanguagePack
+    UnlistedEntries(Vec<String>),
+    /// Config could not be turned into the stored format
+    Serialization(String),
+    /// Config file which can't be parsed, @line counts from 1 and is None if it is not known
+    ConfigParse {
+        file: String,
+        line: Option<usize>,
+        message: String,
+    },
 }
 
 impl From<std::io::Error> for LangErr {
@@ -20,3 +61,9 @@ impl From<std::num::ParseIntError> for LangErr {
         LangErr::ParseIntError(err)
     }
 }
+
+impl From<rusqlite::Error> for LangErr {
+    fn from(err: rusqlite::Error) -> Self {
+        LangErr::Sqlite(err)
+    }
+}
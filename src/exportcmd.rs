@@ -0,0 +1,55 @@
+use crate::config::LangConfig;
+use crate::error::LangErr::{Serialization, UnlistedEntries};
+use crate::languagepack::LanguagePack;
+use crate::Result;
+use crate::TakeAppArg;
+use clap::ArgMatches;
+use std::fs;
+
+pub struct ExportCmd;
+
+const SUBCOMMAND: &str = "export";
+
+impl ExportCmd {
+    pub fn new() -> ExportCmd {
+        ExportCmd
+    }
+}
+
+impl TakeAppArg for ExportCmd {
+    fn subcommand(&self) -> &str {
+        SUBCOMMAND
+    }
+
+    fn do_exec(&mut self, arguments: &ArgMatches, cfg: Box<dyn LangConfig>) -> Result<()> {
+        let left_out = LanguagePack::left_out(cfg.as_ref());
+        if !left_out.is_empty() {
+            return Err(UnlistedEntries(left_out));
+        }
+
+        let mut pack = LanguagePack::from_config(cfg.as_ref())?;
+        if let Some(info) = cfg.info() {
+            pack.language = info.clone();
+        }
+        if pack.language.name.is_none() {
+            pack.language.name = cfg
+                .dir()
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string());
+        }
+
+        // Json is the only format so far
+        let content =
+            serde_json::to_string_pretty(&pack).map_err(|e| Serialization(e.to_string()))?;
+
+        match arguments.value_of("output") {
+            Some(path) => {
+                fs::write(path, content)?;
+                eprintln!("Language was exported to {}", path);
+            }
+            None => println!("{}", content),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::error::LangErr::MissingFile;
+use crate::Result;
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+/// File inside a language directory locked while the language is changed
+const LOCK_FILE_NAME: &str = ".langgen.lock";
+
+/// Counts temporary files written by this process, see write_atomically
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces file @path with @content. Content is written to a temporary file first which is
+/// then renamed, so a crash leaves either the old or the new file but never a truncated one.
+/// Temporary files are named after the process and a counter, so writers don't share one
+pub fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut tmp = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)?;
+    tmp.write_all(content)?;
+    tmp.sync_all()?;
+
+    Ok(fs::rename(&tmp_path, path)?)
+}
+
+/// What a file looked like when it was read, used to find out it was changed since
+#[derive(PartialEq, Clone, Copy)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Stamp of file @path, None if it does not exist
+pub fn stamp(path: &Path) -> Option<FileStamp> {
+    fs::metadata(path).ok().map(|m| FileStamp {
+        modified: m.modified().ok(),
+        len: m.len(),
+    })
+}
+
+/// Advisory lock of a language directory, other processes taking it wait until it is dropped
+pub struct DirLock {
+    _file: File, // Lock is released when the file is closed
+}
+
+impl DirLock {
+    /// Takes the lock of @dir, waits while it is held by another process
+    pub fn acquire(dir: &Path) -> Result<DirLock> {
+        if !dir.is_dir() {
+            return Err(MissingFile(dir.display().to_string()));
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(LOCK_FILE_NAME))?;
+        file.lock_exclusive()?;
+
+        Ok(DirLock { _file: file })
+    }
+}
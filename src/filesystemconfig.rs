@@ -1,149 +1,446 @@
 use crate::config::LangConfig;
+use crate::error::LangErr::{ConfigChanged, ConfigParse, MissingFile};
+use crate::filesafety::{self, FileStamp};
+use crate::lexicon::LexiconEntry;
+use crate::lint::{self, LintIssue};
+use crate::phonology::Phonology;
+use crate::phonotactics::AdjacencyRule;
 use crate::syllables::SyllablePosition;
+use crate::tuning::Tuning;
+use crate::wanted::Wanted;
 use crate::Result;
-use app_dirs::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-
-const APP_INFO: AppInfo = AppInfo {
-    name: "LangGen",
-    author: "LukxNet",
-};
+use std::io;
+use std::path::{Path, PathBuf};
 
 const SYLLABLES_NAME: &str = "Syllables.txt";
 const OCC_WANTED_NAME: &str = "Wanted.txt";
 const SYLLABLE_VALID_POS_NAME: &str = "SyllablePos.txt";
 const WORD_DATABASE_NAME: &str = "Word_Database.txt";
 const SYLLABLES_TO_UTF8_NAME: &str = "SyllablesToUTF8.txt";
+const PHONOLOGY_NAME: &str = "Phonology.txt";
+const ADJACENCY_NAME: &str = "Adjacency.txt";
+const TUNING_NAME: &str = "Tuning.txt";
+
+/// Files every language must have
+const REQUIRED_FILE_NAMES: [&str; 5] = [
+    SYLLABLES_NAME,
+    OCC_WANTED_NAME,
+    SYLLABLE_VALID_POS_NAME,
+    WORD_DATABASE_NAME,
+    SYLLABLES_TO_UTF8_NAME,
+];
+
+/// Files a language may do without
+const OPTIONAL_FILE_NAMES: [&str; 3] = [PHONOLOGY_NAME, ADJACENCY_NAME, TUNING_NAME];
+
+/// Creates required files which don't exist yet in @dir, all of them empty
+pub fn create_empty_files(dir: &Path) -> Result<()> {
+    for name in REQUIRED_FILE_NAMES.iter() {
+        let path = dir.join(name);
+        if !path.exists() {
+            fs::write(path, "")?;
+        }
+    }
 
-fn get_cfg_root() -> PathBuf {
-    app_root(AppDataType::UserConfig, &APP_INFO).unwrap()
-}
-
-fn get_syllables_path() -> PathBuf {
-    get_cfg_root().join(SYLLABLES_NAME)
-}
-
-fn get_occ_wanted_path() -> PathBuf {
-    get_cfg_root().join(OCC_WANTED_NAME)
-}
-
-fn get_syllables_valid_pos_path() -> PathBuf {
-    get_cfg_root().join(SYLLABLE_VALID_POS_NAME)
-}
-
-fn get_database_path() -> PathBuf {
-    get_cfg_root().join(WORD_DATABASE_NAME)
+    Ok(())
 }
 
-fn get_syllables_to_utf8_path() -> PathBuf {
-    get_cfg_root().join(SYLLABLES_TO_UTF8_NAME)
+/// Checks whether any config file in @dir has something written in it
+pub fn has_config(dir: &Path) -> bool {
+    REQUIRED_FILE_NAMES
+        .iter()
+        .chain(OPTIONAL_FILE_NAMES.iter())
+        .filter_map(|name| fs::metadata(dir.join(name)).ok())
+        .any(|metadata| metadata.len() > 0)
 }
 
-/// PathBuf to String, unsafe
-fn pbts(p: PathBuf) -> String {
-    p.canonicalize().unwrap().to_str().unwrap().to_string()
+/// Language config stored as text files in a single directory
+pub struct FileSystemConfig {
+    // DATA
+    syllables: Vec<String>,
+    syllable_pos: HashMap<String, SyllablePosition>,
+    utf8_to_ascii: HashMap<String, String>,
+    wanted: HashMap<String, Wanted>,
+    phonology: Phonology,
+    adjacency_rules: Vec<AdjacencyRule>,
+    tuning: Tuning,
+    database: Vec<LexiconEntry>,
+    // Names of files whose data was replaced, only these are written back on flush
+    changed_files: HashSet<&'static str>,
+    // (File name -> stamp when it was read), None if it did not exist
+    loaded_stamps: HashMap<&'static str, Option<FileStamp>>,
+    // FILE PATHS
+    dir: PathBuf,
+    syllables_path: PathBuf,
+    syllable_pos_path: PathBuf,
+    utf8_to_ascii_path: PathBuf,
+    wanted_path: PathBuf,
+    database_path: PathBuf,
+    phonology_path: PathBuf,
+    adjacency_path: PathBuf,
+    tuning_path: PathBuf,
 }
 
-impl Default for FileSystemConfig {
-    fn default() -> Self {
+impl FileSystemConfig {
+    /// Config of a language stored in @dir, nothing is read until load
+    pub fn in_dir(dir: &Path) -> FileSystemConfig {
         FileSystemConfig {
             syllables: Vec::new(),
             syllable_pos: HashMap::new(),
             utf8_to_ascii: HashMap::new(),
             wanted: HashMap::new(),
+            phonology: Phonology::default(),
+            adjacency_rules: Vec::new(),
+            tuning: Tuning::default(),
             database: Vec::new(),
-            syllables_path: pbts(get_syllables_path()),
-            wanted_path: pbts(get_occ_wanted_path()),
-            syllable_pos_path: pbts(get_syllables_valid_pos_path()),
-            database_path: pbts(get_database_path()),
-            utf8_to_ascii_path: pbts(get_syllables_to_utf8_path()),
+            changed_files: HashSet::new(),
+            loaded_stamps: HashMap::new(),
+            dir: dir.to_path_buf(),
+            syllables_path: dir.join(SYLLABLES_NAME),
+            wanted_path: dir.join(OCC_WANTED_NAME),
+            syllable_pos_path: dir.join(SYLLABLE_VALID_POS_NAME),
+            database_path: dir.join(WORD_DATABASE_NAME),
+            utf8_to_ascii_path: dir.join(SYLLABLES_TO_UTF8_NAME),
+            phonology_path: dir.join(PHONOLOGY_NAME), // Optional, may not exist
+            adjacency_path: dir.join(ADJACENCY_NAME), // Optional, may not exist
+            tuning_path: dir.join(TUNING_NAME),       // Optional, may not exist
         }
     }
-}
 
-pub struct FileSystemConfig {
-    // DATA
-    syllables: Vec<String>,
-    syllable_pos: HashMap<String, SyllablePosition>,
-    utf8_to_ascii: HashMap<String, String>,
-    wanted: HashMap<String, f64>,
-    database: Vec<String>,
-    // FILE PATHS
-    syllables_path: String,
-    syllable_pos_path: String,
-    utf8_to_ascii_path: String,
-    wanted_path: String,
-    database_path: String,
-}
-
-impl FileSystemConfig {
     fn load_syllables(&mut self) -> Result<Vec<String>> {
-        Ok(fs::read_to_string(&self.syllables_path)?
+        Ok(read_file(&self.syllables_path)?
             .lines()
             .map(|s| s.to_string())
             .collect())
     }
     fn load_syllable_pos(&mut self) -> Result<HashMap<String, SyllablePosition>> {
-        let mut result = HashMap::new();
-
-        fs::read_to_string(&self.syllable_pos_path)?
-            .lines()
-            .for_each(|line| {
-                let res: Vec<&str> = line.split(':').collect();
-                let syllable = res[0].to_string();
-                let pos = res[1].to_string().into();
-                result.insert(syllable, pos);
-            });
-
-        Ok(result)
+        Ok(parse_lines(&self.syllable_pos_path, |line| {
+            let (syllable, keyword) = split_pair(line)?;
+            match SyllablePosition::from_keyword(keyword) {
+                Some(pos) => Ok((syllable.to_string(), pos)),
+                None => Err(format!("unknown position keyword '{}'", keyword)),
+            }
+        })?
+        .into_iter()
+        .collect())
     }
     fn load_utf8_to_ascii(&mut self) -> Result<HashMap<String, String>> {
-        Ok(fs::read_to_string(&self.utf8_to_ascii_path)?
-            .lines()
-            .map(parse_colon_separated_str_str)
+        Ok(parse_lines(&self.utf8_to_ascii_path, parse_str_str)?
+            .into_iter()
             .collect())
     }
-    fn load_wanted(&mut self) -> Result<HashMap<String, f64>> {
-        Ok(fs::read_to_string(&self.wanted_path)?
-            .lines()
-            .map(parse_colon_separated_str_f64)
-            .collect())
+    fn load_wanted(&mut self) -> Result<HashMap<String, Wanted>> {
+        Ok(parse_lines(&self.wanted_path, |line| {
+            let (syllable, value) = split_pair(line)?;
+            Ok((syllable.to_string(), Wanted::from_value(value)?))
+        })?
+        .into_iter()
+        .collect())
     }
-    fn load_database(&mut self) -> Result<Vec<String>> {
-        Ok(fs::read_to_string(&self.database_path)?
+    fn load_phonology(&mut self) -> Result<Phonology> {
+        let mut phonology = Phonology::default();
+
+        if !self.phonology_path.exists() {
+            return Ok(phonology); // Phonology is not required
+        }
+
+        for (key, value) in parse_lines(&self.phonology_path, parse_phonology_line)? {
+            match key {
+                None => phonology.templates.push(value),
+                Some(class) => {
+                    let phonemes = value.split(',').map(|p| p.trim().to_string()).collect();
+                    phonology.classes.insert(class, phonemes);
+                }
+            }
+        }
+
+        Ok(phonology)
+    }
+    fn load_adjacency_rules(&mut self) -> Result<Vec<AdjacencyRule>> {
+        if !self.adjacency_path.exists() {
+            return Ok(Vec::new()); // Rules are not required
+        }
+
+        parse_lines(&self.adjacency_path, AdjacencyRule::from_line)
+    }
+    fn load_tuning(&mut self) -> Result<Tuning> {
+        let mut tuning = Tuning::default();
+
+        if !self.tuning_path.exists() {
+            return Ok(tuning); // Default tuning is used
+        }
+
+        for (key, value) in parse_lines(&self.tuning_path, parse_tuning_line)? {
+            tuning.set(&key, &value).unwrap(); // Checked when parsing
+        }
+
+        Ok(tuning)
+    }
+    fn load_database(&mut self) -> Result<Vec<LexiconEntry>> {
+        Ok(read_file(&self.database_path)?
             .lines()
-            .map(|s| s.to_string())
+            .filter(|line| !line.is_empty())
+            .map(LexiconEntry::from_line)
             .collect())
     }
 
+    /// Lines of file @name with their numbers, None and an issue if the file does not exist
+    fn lint_lines(
+        &self,
+        name: &str,
+        issues: &mut Vec<LintIssue>,
+    ) -> Result<Option<Vec<(usize, String)>>> {
+        match read_file(&self.dir.join(name)) {
+            Ok(content) => Ok(Some(
+                content
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| (i + 1, line.to_string()))
+                    .collect(),
+            )),
+            Err(MissingFile(_)) => {
+                issues.push(LintIssue::new(
+                    name,
+                    None,
+                    "file does not exist".to_string(),
+                ));
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Checks a file of `syllable:value` lines against @syllables. Returns well formed lines
+    /// as (line number, syllable, value), @check_value tells what is wrong with a value
+    fn lint_syllable_map<F>(
+        &self,
+        name: &str,
+        syllables: &HashSet<String>,
+        check_value: F,
+        issues: &mut Vec<LintIssue>,
+    ) -> Result<Vec<(usize, String, String)>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut entries: Vec<(usize, String, String)> = Vec::new();
+
+        for (line_no, line) in self.lint_lines(name, issues)?.unwrap_or_default() {
+            if line.trim().is_empty() {
+                continue; // Loaders skip blank lines too
+            }
+
+            let (syllable, value) = match split_pair(&line) {
+                Ok(pair) => pair,
+                Err(message) => {
+                    issues.push(LintIssue::new(name, Some(line_no), message));
+                    continue;
+                }
+            };
+
+            if let Some(message) = check_value(value) {
+                issues.push(LintIssue::new(name, Some(line_no), message));
+            }
+            if let Some((first, _, _)) = entries.iter().find(|(_, s, _)| s == syllable) {
+                issues.push(LintIssue::new(
+                    name,
+                    Some(line_no),
+                    format!("{} is already listed on line {}", syllable, first),
+                ));
+            }
+            if !syllables.contains(syllable) {
+                issues.push(LintIssue::new(
+                    name,
+                    Some(line_no),
+                    format!("{} is not listed in {}", syllable, SYLLABLES_NAME),
+                ));
+            }
+
+            entries.push((line_no, syllable.to_string(), value.to_string()));
+        }
+
+        Ok(entries)
+    }
+
     fn write_database(&mut self) -> Result<()> {
-        let mut db = self.database.join("\n");
-        db.push('\n');
+        let lines: Vec<String> = self.database.iter().map(LexiconEntry::to_line).collect();
+        write_lines(&self.database_path, &lines)
+    }
+
+    fn write_syllables(&mut self) -> Result<()> {
+        write_lines(&self.syllables_path, &self.syllables)
+    }
+
+    fn write_syllable_pos(&mut self) -> Result<()> {
+        let lines: Vec<String> = self
+            .keys_in_syllable_order(self.syllable_pos.keys())
+            .iter()
+            .map(|s| format!("{}:{}", s, self.syllable_pos[s]))
+            .collect();
+        write_lines(&self.syllable_pos_path, &lines)
+    }
+
+    fn write_utf8_to_ascii(&mut self) -> Result<()> {
+        let lines: Vec<String> = self
+            .keys_in_syllable_order(self.utf8_to_ascii.keys())
+            .iter()
+            .map(|s| format!("{}:{}", s, self.utf8_to_ascii[s]))
+            .collect();
+        write_lines(&self.utf8_to_ascii_path, &lines)
+    }
+
+    fn write_wanted(&mut self) -> Result<()> {
+        let lines: Vec<String> = self
+            .keys_in_syllable_order(self.wanted.keys())
+            .iter()
+            .map(|s| format!("{}:{}", s, self.wanted[s]))
+            .collect();
+        write_lines(&self.wanted_path, &lines)
+    }
+
+    fn write_phonology(&mut self) -> Result<()> {
+        let mut lines: Vec<String> = self
+            .phonology
+            .classes
+            .iter()
+            .map(|(class, phonemes)| format!("{}:{}", class, phonemes.join(",")))
+            .collect();
+        lines.extend(
+            self.phonology
+                .templates
+                .iter()
+                .map(|t| format!("template:{}", t)),
+        );
+        write_lines(&self.phonology_path, &lines)
+    }
+
+    fn write_adjacency_rules(&mut self) -> Result<()> {
+        let lines: Vec<String> = self.adjacency_rules.iter().map(|r| r.to_string()).collect();
+        write_lines(&self.adjacency_path, &lines)
+    }
+
+    fn write_tuning(&mut self) -> Result<()> {
+        let lines: Vec<String> = self
+            .tuning
+            .pairs()
+            .iter()
+            .map(|(key, value)| format!("{}:{}", key, value))
+            .collect();
+        write_lines(&self.tuning_path, &lines)
+    }
+
+    /// Keys of a syllable map in the order of the syllables file, so written files
+    /// read the same way. Keys which are not syllables come last, sorted
+    fn keys_in_syllable_order<'a, I>(&self, keys: I) -> Vec<String>
+    where
+        I: Iterator<Item = &'a String>,
+    {
+        let mut others: Vec<String> = Vec::new();
+        let mut known: Vec<String> = Vec::new();
+
+        for key in keys {
+            if self.syllables.contains(key) {
+                known.push(key.to_string());
+            } else {
+                others.push(key.to_string());
+            }
+        }
+
+        known.sort_by_key(|k| self.syllables.iter().position(|s| s == k));
+        others.sort();
+        known.extend(others);
+
+        known
+    }
+}
+
+/// Writes every line followed by a line break
+fn write_lines(path: &Path, lines: &[String]) -> Result<()> {
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+
+    filesafety::write_atomically(path, content.as_bytes())
+}
+
+/// Reads a whole file, a missing file is reported with its path
+pub fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => MissingFile(path.display().to_string()),
+        _ => e.into(),
+    })
+}
 
-        Ok(fs::write(&self.database_path, db)?)
+/// Splits a `key:value` line, error tells what is wrong with the line
+fn split_pair(line: &str) -> std::result::Result<(&str, &str), String> {
+    let mut split = line.splitn(2, ':');
+    let key = split.next().unwrap_or_default();
+
+    match split.next() {
+        None => Err("expected ':' separating two values".to_string()),
+        Some(_) if key.is_empty() => Err("expected value before ':'".to_string()),
+        Some("") => Err("expected value after ':'".to_string()),
+        Some(value) if value.contains(':') => Err("expected only one ':'".to_string()),
+        Some(value) => Ok((key, value)),
     }
 }
 
-//TODO: Return results
-fn parse_colon_separated_str_str(line: &str) -> (String, String) {
-    let mut split = line.split(':');
+/// Parses every line of file @path which is not blank. Errors of @parse are reported
+/// together with the file name and line
+fn parse_lines<T, F>(path: &Path, parse: F) -> Result<Vec<T>>
+where
+    F: Fn(&str) -> std::result::Result<T, String>,
+{
+    let file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    read_file(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse(line).map_err(|message| ConfigParse {
+                file: file.clone(),
+                line: Some(i + 1),
+                message,
+            })
+        })
+        .collect()
+}
 
-    (
-        split.next().unwrap().to_string(),
-        split.next().unwrap().to_string(),
-    )
+fn parse_str_str(line: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = split_pair(line)?;
+    Ok((key.to_string(), value.to_string()))
 }
 
-//TODO: Return results
-fn parse_colon_separated_str_f64(line: &str) -> (String, f64) {
-    let mut split = line.split(':');
+/// Parses a line of the tuning file, the value is checked but not kept
+fn parse_tuning_line(line: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = split_pair(line)?;
+    Tuning::default().set(key, value)?;
 
-    (
-        split.next().unwrap().to_string(),
-        split.next().unwrap().to_string().parse().unwrap(),
-    )
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Parses a line of the phonology file, the key is None for a template
+fn parse_phonology_line(line: &str) -> std::result::Result<(Option<char>, String), String> {
+    let (key, value) = split_pair(line)?;
+
+    if key == "template" {
+        return Ok((None, value.to_string()));
+    }
+
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(class), None) => Ok((Some(class), value.to_string())),
+        _ => Err(format!(
+            "expected 'template' or a single char phoneme class instead of '{}'",
+            key
+        )),
+    }
 }
 
 impl LangConfig for FileSystemConfig {
@@ -153,6 +450,7 @@ impl LangConfig for FileSystemConfig {
 
     fn set_syllables(&mut self, syllables: Vec<String>) {
         self.syllables = syllables;
+        self.changed_files.insert(SYLLABLES_NAME);
     }
 
     fn syllable_pos(&self) -> &HashMap<String, SyllablePosition> {
@@ -160,7 +458,8 @@ impl LangConfig for FileSystemConfig {
     }
 
     fn set_syllable_pos(&mut self, syllable_pos: HashMap<String, SyllablePosition>) {
-        self.syllable_pos = syllable_pos
+        self.syllable_pos = syllable_pos;
+        self.changed_files.insert(SYLLABLE_VALID_POS_NAME);
     }
 
     fn romanization(&self) -> &HashMap<String, String> {
@@ -169,43 +468,257 @@ impl LangConfig for FileSystemConfig {
 
     fn set_romanization(&mut self, utf_to_ascii: HashMap<String, String>) {
         self.utf8_to_ascii = utf_to_ascii;
+        self.changed_files.insert(SYLLABLES_TO_UTF8_NAME);
     }
 
-    fn wanted(&self) -> &HashMap<String, f64> {
+    fn wanted(&self) -> &HashMap<String, Wanted> {
         &self.wanted
     }
 
-    fn set_wanted(&mut self, wanted: HashMap<String, f64>) {
+    fn set_wanted(&mut self, wanted: HashMap<String, Wanted>) {
         self.wanted = wanted;
+        self.changed_files.insert(OCC_WANTED_NAME);
+    }
+
+    fn phonology(&self) -> &Phonology {
+        &self.phonology
+    }
+
+    fn set_phonology(&mut self, phonology: Phonology) {
+        self.phonology = phonology;
+        self.changed_files.insert(PHONOLOGY_NAME);
+    }
+
+    fn adjacency_rules(&self) -> &Vec<AdjacencyRule> {
+        &self.adjacency_rules
+    }
+
+    fn set_adjacency_rules(&mut self, rules: Vec<AdjacencyRule>) {
+        self.adjacency_rules = rules;
+        self.changed_files.insert(ADJACENCY_NAME);
+    }
+
+    fn tuning(&self) -> &Tuning {
+        &self.tuning
     }
 
-    fn database(&self) -> &Vec<String> {
-        &self.database
+    fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+        self.changed_files.insert(TUNING_NAME);
     }
 
-    fn set_database(&mut self, db: Vec<String>) {
+    fn database(&self) -> Result<&Vec<LexiconEntry>> {
+        Ok(&self.database)
+    }
+
+    fn set_database(&mut self, db: Vec<LexiconEntry>) {
         self.database = db;
     }
 
-    fn append_database(&mut self, words: &[String]) {
-        words.iter().for_each(|w| self.database.push(w.to_string()));
+    fn append_database(&mut self, entries: &[LexiconEntry]) {
+        self.database.extend_from_slice(entries);
     }
 
-    fn delete_from_database(&mut self, word: &str) -> bool {
-        self.database.remove_item(&word.to_string()).is_some()
+    fn delete_from_database(&mut self, word: &str) -> Result<Option<LexiconEntry>> {
+        match self.database.iter().position(|e| e.word == word) {
+            Some(index) => Ok(Some(self.database.remove(index))),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_entry_from_database(&mut self, entry: &LexiconEntry) -> Result<bool> {
+        match self.database.iter().position(|e| e == entry) {
+            Some(index) => {
+                self.database.remove(index);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
 
     fn load(&mut self) -> Result<()> {
+        for name in REQUIRED_FILE_NAMES.iter().chain(OPTIONAL_FILE_NAMES.iter()) {
+            // Taken before reading, so a change made while reading is noticed too
+            self.loaded_stamps
+                .insert(name, filesafety::stamp(&self.dir.join(name)));
+        }
+
         self.syllables = self.load_syllables()?;
         self.syllable_pos = self.load_syllable_pos()?;
         self.utf8_to_ascii = self.load_utf8_to_ascii()?;
         self.wanted = self.load_wanted()?;
+        self.phonology = self.load_phonology()?;
+        self.adjacency_rules = self.load_adjacency_rules()?;
+        self.tuning = self.load_tuning()?;
         self.database = self.load_database()?;
 
         Ok(())
     }
 
+    fn lint(&self) -> Result<Vec<LintIssue>> {
+        let mut issues = Vec::new();
+
+        // Syllables with the line they are first listed on
+        let mut listed: Vec<(usize, String)> = Vec::new();
+        for (line_no, syllable) in self
+            .lint_lines(SYLLABLES_NAME, &mut issues)?
+            .unwrap_or_default()
+        {
+            let message = if syllable.trim().is_empty() {
+                Some("empty line".to_string())
+            } else if syllable.trim() != syllable {
+                Some(format!("'{}' has whitespace around it", syllable))
+            } else {
+                listed
+                    .iter()
+                    .find(|(_, s)| *s == syllable)
+                    .map(|(first, _)| format!("{} is already listed on line {}", syllable, first))
+            };
+
+            match message {
+                Some(message) => {
+                    issues.push(LintIssue::new(SYLLABLES_NAME, Some(line_no), message))
+                }
+                None => listed.push((line_no, syllable)),
+            }
+        }
+        let syllables: HashSet<String> = listed.iter().map(|(_, s)| s.clone()).collect();
+
+        let positions = self.lint_syllable_map(
+            SYLLABLE_VALID_POS_NAME,
+            &syllables,
+            |value| match SyllablePosition::from_keyword(value) {
+                Some(_) => None,
+                None => Some(format!("unknown position keyword '{}'", value)),
+            },
+            &mut issues,
+        )?;
+        let utf8_forms =
+            self.lint_syllable_map(SYLLABLES_TO_UTF8_NAME, &syllables, |_| None, &mut issues)?;
+        let wanted = self.lint_syllable_map(
+            OCC_WANTED_NAME,
+            &syllables,
+            |value| Wanted::from_value(value).err(),
+            &mut issues,
+        )?;
+
+        let wanted_values: Vec<Wanted> = wanted
+            .iter()
+            .filter_map(|(_, _, value)| Wanted::from_value(value).ok())
+            .collect();
+        for message in lint::wanted_sum_issues(wanted_values.iter()) {
+            issues.push(LintIssue::new(OCC_WANTED_NAME, None, message));
+        }
+
+        for (line_no, syllable) in &listed {
+            for (name, entries) in &[
+                (SYLLABLE_VALID_POS_NAME, &positions),
+                (SYLLABLES_TO_UTF8_NAME, &utf8_forms),
+                (OCC_WANTED_NAME, &wanted),
+            ] {
+                if !entries.iter().any(|(_, s, _)| s == syllable) {
+                    issues.push(LintIssue::new(
+                        SYLLABLES_NAME,
+                        Some(*line_no),
+                        format!("{} is missing from {}", syllable, name),
+                    ));
+                }
+            }
+        }
+
+        let mut words: HashMap<String, usize> = HashMap::new();
+        for (line_no, line) in self
+            .lint_lines(WORD_DATABASE_NAME, &mut issues)?
+            .unwrap_or_default()
+        {
+            if line.is_empty() {
+                continue;
+            }
+
+            let word = LexiconEntry::from_line(&line).word;
+            if let Some(first) = words.get(&word) {
+                issues.push(LintIssue::new(
+                    WORD_DATABASE_NAME,
+                    Some(line_no),
+                    format!("{} is already listed on line {}", word, first),
+                ));
+            } else {
+                words.insert(word, line_no);
+            }
+        }
+
+        if self.tuning_path.exists() {
+            for (line_no, line) in self
+                .lint_lines(TUNING_NAME, &mut issues)?
+                .unwrap_or_default()
+            {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if let Err(message) = parse_tuning_line(&line) {
+                    issues.push(LintIssue::new(TUNING_NAME, Some(line_no), message));
+                }
+            }
+        }
+
+        issues.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+        Ok(issues)
+    }
+
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn remove(&mut self) -> Result<()> {
+        for name in REQUIRED_FILE_NAMES.iter().chain(OPTIONAL_FILE_NAMES.iter()) {
+            let path = self.dir.join(name);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<()> {
-        self.write_database()
+        fs::create_dir_all(&self.dir)?;
+
+        let mut to_write = self.changed_files.clone();
+        to_write.insert(WORD_DATABASE_NAME);
+
+        // Nothing is written if any file would overwrite changes made by someone else
+        for name in &to_write {
+            if let Some(loaded) = self.loaded_stamps.get(name) {
+                let path = self.dir.join(name);
+                if filesafety::stamp(&path) != *loaded {
+                    return Err(ConfigChanged(path.display().to_string()));
+                }
+            }
+        }
+
+        for name in self.changed_files.clone() {
+            match name {
+                SYLLABLES_NAME => self.write_syllables()?,
+                SYLLABLE_VALID_POS_NAME => self.write_syllable_pos()?,
+                SYLLABLES_TO_UTF8_NAME => self.write_utf8_to_ascii()?,
+                OCC_WANTED_NAME => self.write_wanted()?,
+                PHONOLOGY_NAME => self.write_phonology()?,
+                ADJACENCY_NAME => self.write_adjacency_rules()?,
+                TUNING_NAME => self.write_tuning()?,
+                _ => {}
+            }
+        }
+        self.changed_files.clear();
+        self.write_database()?;
+
+        for name in to_write {
+            if self.loaded_stamps.contains_key(name) {
+                self.loaded_stamps
+                    .insert(name, filesafety::stamp(&self.dir.join(name)));
+            }
+        }
+
+        Ok(())
     }
 }
@@ -1,9 +1,14 @@
 use crate::config::LangConfig;
+use crate::error::LangErr::{HistoryUnused, InvalidTuning};
+use crate::journal::{self, JournalEntry};
+use crate::lexicon::LexiconEntry;
 use crate::rangen::RandomEngine;
+use crate::tuning::{self, Tuning};
 use crate::Result;
-use crate::{rangen, syllables, TakeAppArg};
+use crate::{proposals, rangen, syllables, ConfigUse, TakeAppArg};
 use clap::ArgMatches;
 use clipboard::{ClipboardContext, ClipboardProvider};
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 pub const SUB_COMMAND: &str = "gen";
@@ -41,8 +46,28 @@ fn romanize(words: &[String], cfg: &dyn LangConfig) -> Vec<String> {
         .collect()
 }
 
-fn add_to_db(words: &[String], cfg: &mut dyn LangConfig) {
-    cfg.append_database(words);
+fn add_to_db(words: &[String], cfg: &mut dyn LangConfig) -> Result<()> {
+    let entries: Vec<LexiconEntry> = words
+        .iter()
+        .map(|w| LexiconEntry::created_today(w))
+        .collect();
+    cfg.append_database(&entries);
+
+    let dir = cfg.dir().to_path_buf();
+    journal::record(&dir, &JournalEntry::now(entries, Vec::new()), || {
+        cfg.flush()
+    })
+}
+
+/// Records syllables of @words in the proposal history, words which can't be split are left out
+fn record_proposals(words: &[String], cfg: &dyn LangConfig) -> Result<()> {
+    let proposed: Vec<String> = words
+        .iter()
+        .filter_map(|w| syllables::split_into_syllables(w, cfg).ok())
+        .flatten()
+        .collect();
+
+    proposals::record(cfg.dir(), &proposed)
 }
 
 fn copy_to_clipboard(words: &[String]) {
@@ -56,30 +81,113 @@ fn print_words(words: &[String]) {
     words.iter().for_each(|word| println!("{}", word));
 }
 
-fn choose_rangen(args: &ArgMatches, cfg: &dyn LangConfig) -> Box<dyn RandomEngine> {
-    if args.is_present("realrandom") {
-        rangen::real_random(cfg)
+fn choose_rangen(
+    args: &ArgMatches,
+    cfg: &dyn LangConfig,
+    history: &HashMap<String, f64>,
+) -> Result<Box<dyn RandomEngine>> {
+    let seed = match args.value_of("seed") {
+        Some(seed) => Some(seed.parse()?),
+        None => None,
+    };
+
+    let engine = if args.is_present("realrandom") {
+        "real"
     } else {
-        rangen::calculated_random(cfg)
+        value_of_unsafe(args, "engine")
+    };
+
+    // History would be recorded for nothing, only the calculated engine reads it
+    if engine != "calculated" && value_of_unsafe(args, "history") != "off" {
+        return Err(HistoryUnused(engine.to_string()));
+    }
+
+    match engine {
+        "real" => rangen::real_random(cfg, seed),
+        "weighted" => rangen::weighted_random(cfg, seed),
+        "markov" => rangen::markov_random(cfg, value_of_unsafe(args, "order").parse()?, seed),
+        _ => rangen::calculated_random(cfg, tuning_of(args, cfg)?, history, seed),
     }
 }
 
+/// Tuning of @cfg with values given as arguments instead
+fn tuning_of(args: &ArgMatches, cfg: &dyn LangConfig) -> Result<Tuning> {
+    let mut tuning = cfg.tuning().clone();
+
+    for (arg, key) in &tuning::TUNING_ARGS {
+        if let Some(value) = args.value_of(arg) {
+            tuning.set(key, value).map_err(InvalidTuning)?;
+        }
+    }
+
+    Ok(tuning)
+}
+
 impl TakeAppArg for GenerateCmd {
     fn subcommand(&self) -> &str {
         SUB_COMMAND
     }
 
+    fn config_use(&self, arguments: &ArgMatches) -> ConfigUse {
+        if arguments.is_present("db") || value_of_unsafe(arguments, "history") != "off" {
+            ConfigUse::LoadLocked
+        } else {
+            ConfigUse::Load
+        }
+    }
+
     fn do_exec(&mut self, arguments: &ArgMatches, mut cfg: Box<dyn LangConfig>) -> Result<()> {
-        let mut engine = choose_rangen(arguments, cfg.as_ref());
+        let history_use = value_of_unsafe(arguments, "history");
+        if history_use == "reset" {
+            proposals::reset(cfg.dir())?;
+        }
+        let history = match history_use {
+            "off" => HashMap::new(),
+            _ => proposals::read(cfg.dir())?,
+        };
+
+        let mut engine = choose_rangen(arguments, cfg.as_ref(), &history)?;
 
         let (min, max) = calc_min_max(arguments)?;
         let count = value_of_unsafe(arguments, "count").parse()?;
 
-        let mut words = engine.create_words(min, max, count, cfg.as_ref());
+        let min_distance: Option<usize> = match arguments.value_of("min-distance") {
+            Some(distance) => Some(distance.parse()?),
+            None => None,
+        };
+        let unique = arguments.is_present("unique")
+            || min_distance.is_some()
+            || (arguments.is_present("db") && !arguments.is_present("no-unique"));
+
+        let mut words = if let Some(distance) = min_distance {
+            // Split once, not for every candidate. Words which can't be split are skipped
+            let existing: Vec<Vec<String>> = cfg
+                .database()?
+                .iter()
+                .filter_map(|e| syllables::split_into_syllables(&e.word, cfg.as_ref()).ok())
+                .collect();
+            rangen::create_distinct_words(engine.as_mut(), min, max, count, cfg.as_ref(), |w| {
+                match syllables::split_into_syllables(w, cfg.as_ref()) {
+                    Ok(split) => existing
+                        .iter()
+                        .all(|e| syllables::edit_distance(&split, e) > distance),
+                    Err(_) => false,
+                }
+            })?
+        } else if unique {
+            let existing: HashSet<&str> = cfg.database()?.iter().map(|e| e.word.as_str()).collect();
+            rangen::create_distinct_words(engine.as_mut(), min, max, count, cfg.as_ref(), |w| {
+                !existing.contains(w)
+            })?
+        } else {
+            engine.create_words(min, max, count, cfg.as_ref())?
+        };
 
         if arguments.is_present("db") {
-            add_to_db(&words, cfg.as_mut());
-            cfg.flush()?;
+            add_to_db(&words, cfg.as_mut())?;
+        } else if history_use != "off" {
+            // Words added to the database are counted from there
+            record_proposals(&words, cfg.as_ref())?;
         }
 
         if arguments.is_present("romanize") {
@@ -0,0 +1,68 @@
+use crate::backend;
+use crate::config::LangConfig;
+use crate::error::LangErr::{ConfigExists, ConfigParse};
+use crate::filesystemconfig::read_file;
+use crate::journal::{self, JournalEntry};
+use crate::languagepack::LanguagePack;
+use crate::proposals;
+use crate::Result;
+use crate::{ConfigUse, TakeAppArg};
+use clap::ArgMatches;
+
+pub struct ImportCmd;
+
+const SUBCOMMAND: &str = "import";
+
+impl ImportCmd {
+    pub fn new() -> ImportCmd {
+        ImportCmd
+    }
+}
+
+impl TakeAppArg for ImportCmd {
+    fn subcommand(&self) -> &str {
+        SUBCOMMAND
+    }
+
+    fn config_use(&self, _arguments: &ArgMatches) -> ConfigUse {
+        ConfigUse::UnloadedLocked // Everything is replaced
+    }
+
+    fn do_exec(&mut self, arguments: &ArgMatches, mut cfg: Box<dyn LangConfig>) -> Result<()> {
+        let path = arguments.value_of("file").unwrap();
+
+        if !arguments.is_present("force") && backend::has_config(cfg.dir()) {
+            return Err(ConfigExists(cfg.dir().display().to_string()));
+        }
+
+        let pack: LanguagePack = serde_json::from_str(&read_file(path.as_ref())?).map_err(|e| {
+            // Position is reported as a line of its own
+            let mut message = e.to_string();
+            if let Some(i) = message.find(" at line ") {
+                message.truncate(i);
+            }
+
+            ConfigParse {
+                file: path.to_string(),
+                line: Some(e.line()).filter(|line| *line > 0),
+                message,
+            }
+        })?;
+
+        let syllables = pack.syllables.len();
+        let words = pack.words.len();
+        pack.apply_to(cfg.as_mut());
+        let dir = cfg.dir().to_path_buf();
+        journal::record(&dir, &JournalEntry::replacement(), || cfg.flush())?;
+        proposals::reset(&dir)?; // Proposed syllables of the replaced language mean nothing now
+
+        println!(
+            "Imported {} syllables and {} words into {}",
+            syllables,
+            words,
+            cfg.dir().display()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::backend;
+use crate::config::LangConfig;
+use crate::error::LangErr::ConfigExists;
+use crate::journal::{self, JournalEntry};
+use crate::proposals;
+use crate::starter::Starter;
+use crate::Result;
+use crate::{ConfigUse, TakeAppArg};
+use clap::ArgMatches;
+
+pub struct InitCmd;
+
+const SUBCOMMAND: &str = "init";
+
+impl InitCmd {
+    pub fn new() -> InitCmd {
+        InitCmd
+    }
+}
+
+impl TakeAppArg for InitCmd {
+    fn subcommand(&self) -> &str {
+        SUBCOMMAND
+    }
+
+    fn config_use(&self, _arguments: &ArgMatches) -> ConfigUse {
+        ConfigUse::UnloadedLocked // Files usually don't exist yet
+    }
+
+    fn do_exec(&mut self, arguments: &ArgMatches, mut cfg: Box<dyn LangConfig>) -> Result<()> {
+        let template = arguments.value_of("template").unwrap();
+
+        if !arguments.is_present("force") && backend::has_config(cfg.dir()) {
+            return Err(ConfigExists(cfg.dir().display().to_string()));
+        }
+
+        Starter::named(template).unwrap().apply(cfg.as_mut())?; // Clap allows only known names
+        let dir = cfg.dir().to_path_buf();
+        journal::record(&dir, &JournalEntry::replacement(), || cfg.flush())?;
+        proposals::reset(&dir)?; // Proposed syllables of the replaced language mean nothing now
+
+        println!(
+            "Language was created from template {} in {}, {} syllables",
+            template,
+            cfg.dir().display(),
+            cfg.syllables().len()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error::LangErr::{ConfigParse, Serialization};
+use crate::filesafety;
+use crate::lexicon::LexiconEntry;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// File inside a language directory recording every change of the word database
+const JOURNAL_NAME: &str = "Journal.jsonl";
+
+/// Single change of the word database, stored as one line of JSON
+#[derive(Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Local time of the change, RFC 3339
+    pub time: String,
+    /// Command line which made the change
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<LexiconEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<LexiconEntry>,
+    /// Numbers of entries this one undoes, see read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub undoes: Vec<usize>,
+    /// Whole database was replaced, changes recorded before can't be undone
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub replaced: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl JournalEntry {
+    /// Entry of a change made now by the running command
+    pub fn now(added: Vec<LexiconEntry>, removed: Vec<LexiconEntry>) -> JournalEntry {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+
+        JournalEntry {
+            time: chrono::Local::now()
+                .format("%Y-%m-%dT%H:%M:%S%:z")
+                .to_string(),
+            command: args.join(" "),
+            added,
+            removed,
+            undoes: Vec::new(),
+            replaced: false,
+        }
+    }
+
+    /// Entry of the running command replacing the whole database, e.g. by importing
+    pub fn replacement() -> JournalEntry {
+        JournalEntry {
+            replaced: true,
+            ..JournalEntry::now(Vec::new(), Vec::new())
+        }
+    }
+
+    /// Checks whether the entry records any change
+    fn changes_something(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty() || self.replaced
+    }
+}
+
+/// Records @entry in the journal of the language in @dir before @write makes the change,
+/// so a change is never made without being recorded. The entry is taken back if @write
+/// fails. A crash while writing leaves the entry, its change may then be made only partly
+pub fn record<F>(dir: &Path, entry: &JournalEntry, write: F) -> Result<()>
+where
+    F: FnOnce() -> Result<()>,
+{
+    append(dir, entry)?;
+
+    if let Err(e) = write() {
+        if entry.changes_something() {
+            remove_last(dir)?;
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Appends @entry to the journal of the language in @dir. Nothing is recorded if
+/// the entry does not change anything
+fn append(dir: &Path, entry: &JournalEntry) -> Result<()> {
+    if !entry.changes_something() {
+        return Ok(());
+    }
+
+    let mut line = serde_json::to_string(entry).map_err(|e| Serialization(e.to_string()))?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(JOURNAL_NAME))?;
+
+    Ok(file.write_all(line.as_bytes())?)
+}
+
+/// Removes the entry appended last to the journal of the language in @dir
+fn remove_last(dir: &Path) -> Result<()> {
+    let path = dir.join(JOURNAL_NAME);
+    let content = fs::read_to_string(&path)?;
+
+    // Every entry ends with a newline, the one before it ends the entry before
+    let kept = match content.trim_end_matches('\n').rfind('\n') {
+        Some(end) => &content[..=end],
+        None => "",
+    };
+
+    filesafety::write_atomically(&path, kept.as_bytes())
+}
+
+/// Every entry of the journal of the language in @dir, oldest first. Entries are
+/// numbered by their position counted from 1
+pub fn read(dir: &Path) -> Result<Vec<JournalEntry>> {
+    let content = match fs::read_to_string(dir.join(JOURNAL_NAME)) {
+        Ok(content) => content,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| ConfigParse {
+                file: JOURNAL_NAME.to_string(),
+                line: Some(i + 1),
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Number of the last entry which replaced the whole database, 0 if there is none.
+/// Only entries after it can be undone
+pub fn last_replacement(entries: &[JournalEntry]) -> usize {
+    entries
+        .iter()
+        .rposition(|e| e.replaced)
+        .map_or(0, |i| i + 1)
+}
+
+/// Numbers of entries which were undone
+pub fn undone(entries: &[JournalEntry]) -> HashSet<usize> {
+    entries
+        .iter()
+        .flat_map(|e| e.undoes.iter().cloned())
+        .collect()
+}
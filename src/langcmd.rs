@@ -0,0 +1,101 @@
+use crate::config::LangConfig;
+use crate::filesystemconfig;
+use crate::profile;
+use crate::Result;
+use crate::{ConfigUse, TakeAppArg};
+use clap::ArgMatches;
+
+pub struct LangCmd;
+
+const SUBCOMMAND: &str = "lang";
+
+impl LangCmd {
+    pub fn new() -> LangCmd {
+        LangCmd
+    }
+}
+
+fn list(_matches: &ArgMatches) -> Result<()> {
+    let names = profile::list()?;
+
+    if names.is_empty() {
+        println!(
+            "No languages were created, config in {} is used",
+            profile::app_root_dir().display()
+        );
+        return Ok(());
+    }
+
+    let default = profile::default_lang()?;
+    for name in names {
+        if default.as_ref() == Some(&name) {
+            println!("* {}", name);
+        } else {
+            println!("  {}", name);
+        }
+    }
+
+    Ok(())
+}
+
+fn create(matches: &ArgMatches) -> Result<()> {
+    let name = matches.value_of("name").unwrap();
+
+    let dir = profile::create(name)?;
+    filesystemconfig::create_empty_files(&dir)?;
+
+    println!("Language {} was created in {}", name, dir.display());
+
+    Ok(())
+}
+
+fn switch(matches: &ArgMatches) -> Result<()> {
+    let name = matches.value_of("name").unwrap();
+
+    profile::set_default_lang(name)?;
+
+    println!("Language {} is now the default one", name);
+
+    Ok(())
+}
+
+fn delete(matches: &ArgMatches) -> Result<()> {
+    let name = matches.value_of("name").unwrap();
+
+    if !matches.is_present("yes") {
+        eprintln!(
+            "Language {} and all of its words would be deleted, confirm with --yes",
+            name
+        );
+        return Ok(());
+    }
+
+    profile::delete(name)?;
+
+    println!("Language {} was deleted", name);
+
+    Ok(())
+}
+
+impl TakeAppArg for LangCmd {
+    fn subcommand(&self) -> &str {
+        SUBCOMMAND
+    }
+
+    fn config_use(&self, _arguments: &ArgMatches) -> ConfigUse {
+        ConfigUse::Nothing
+    }
+
+    fn do_exec(&mut self, arguments: &ArgMatches, _cfg: Box<dyn LangConfig>) -> Result<()> {
+        match arguments.subcommand() {
+            ("list", Some(matches)) => list(matches),
+            ("create", Some(matches)) => create(matches),
+            ("switch", Some(matches)) => switch(matches),
+            ("delete", Some(matches)) => delete(matches),
+            _ => {
+                eprintln!("Invalid or no arguments have been specified");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::config::LangConfig;
+use crate::lexicon::LexiconEntry;
+use crate::phonology::Phonology;
+use crate::phonotactics::AdjacencyRule;
+use crate::syllables::SyllablePosition;
+use crate::tuning::Tuning;
+use crate::wanted::{Wanted, WordPosition};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Whole language in one document, used by formats which store a language in a single file.
+/// Everything known about a syllable is kept together so it can't get out of sync.
+/// Plain values come before tables, some formats can't have it the other way around
+#[derive(Serialize, Deserialize, Default)]
+pub struct LanguagePack {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adjacency: Vec<AdjacencyRule>,
+    #[serde(default)]
+    pub language: LanguageInfo,
+    #[serde(default)]
+    pub phonology: Phonology,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub syllables: Vec<SyllableInfo>,
+    #[serde(default, skip_serializing_if = "Tuning::is_default")]
+    pub tuning: Tuning,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<LexiconEntry>,
+}
+
+/// Describes the language itself
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct LanguageInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Everything known about a single syllable
+#[derive(Serialize, Deserialize)]
+pub struct SyllableInfo {
+    pub syllable: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<SyllablePosition>,
+    /// Form of the syllable written with UTF8 symbols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub romanization: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wanted: Option<f64>,
+    /// Wanted among first syllables of words, the overall one is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wanted_start: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wanted_middle: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wanted_end: Option<f64>,
+}
+
+impl SyllableInfo {
+    /// Info of @syllable, wanted values are taken from @wanted
+    pub fn new(
+        syllable: String,
+        position: Option<SyllablePosition>,
+        romanization: Option<String>,
+        wanted: Option<&Wanted>,
+    ) -> SyllableInfo {
+        SyllableInfo {
+            syllable,
+            position,
+            romanization,
+            wanted: wanted.map(|w| w.overall),
+            wanted_start: wanted.and_then(|w| w.only_at(WordPosition::Start)),
+            wanted_middle: wanted.and_then(|w| w.only_at(WordPosition::Middle)),
+            wanted_end: wanted.and_then(|w| w.only_at(WordPosition::End)),
+        }
+    }
+
+    /// Wanted values of the syllable, None if it has none at all. The overall value
+    /// is 0 if only positional ones are set
+    pub fn wanted_values(&self) -> Option<Wanted> {
+        let positional = [self.wanted_start, self.wanted_middle, self.wanted_end];
+        if self.wanted.is_none() && positional.iter().all(Option::is_none) {
+            return None;
+        }
+
+        Some(Wanted {
+            overall: self.wanted.unwrap_or(0.0),
+            positional,
+        })
+    }
+}
+
+impl LanguagePack {
+    /// Pack of everything in @cfg. Positions, romanization and wanted values
+    /// of syllables which are not listed are left out, see left_out
+    pub fn from_config(cfg: &dyn LangConfig) -> Result<LanguagePack> {
+        Ok(LanguagePack {
+            adjacency: cfg.adjacency_rules().clone(),
+            language: LanguageInfo::default(),
+            phonology: cfg.phonology().clone(),
+            syllables: cfg
+                .syllables()
+                .iter()
+                .map(|s| {
+                    SyllableInfo::new(
+                        s.clone(),
+                        cfg.syllable_pos().get(s).cloned(),
+                        cfg.romanization().get(s).cloned(),
+                        cfg.wanted().get(s),
+                    )
+                })
+                .collect(),
+            tuning: cfg.tuning().clone(),
+            words: cfg.database()?.clone(),
+        })
+    }
+
+    /// Describes entries of @cfg which from_config leaves out, e.g. `position of ka`.
+    /// Empty if nothing is left out
+    pub fn left_out(cfg: &dyn LangConfig) -> Vec<String> {
+        let listed: HashSet<&String> = cfg.syllables().iter().collect();
+        let entries = [
+            ("position", cfg.syllable_pos().keys().collect::<Vec<_>>()),
+            ("romanization", cfg.romanization().keys().collect()),
+            ("wanted value", cfg.wanted().keys().collect()),
+        ];
+
+        let mut left_out: Vec<String> = entries
+            .iter()
+            .flat_map(|(what, syllables)| {
+                syllables
+                    .iter()
+                    .filter(|s| !listed.contains(*s))
+                    .map(move |s| format!("{} of {}", what, s))
+            })
+            .collect();
+        left_out.sort();
+
+        left_out
+    }
+
+    /// Replaces everything in @cfg with the pack, language info is not part of LangConfig
+    pub fn apply_to(self, cfg: &mut dyn LangConfig) {
+        let mut positions = HashMap::new();
+        let mut romanization = HashMap::new();
+        let mut wanted = HashMap::new();
+
+        for info in &self.syllables {
+            if let Some(pos) = &info.position {
+                positions.insert(info.syllable.clone(), pos.clone());
+            }
+            if let Some(r) = &info.romanization {
+                romanization.insert(info.syllable.clone(), r.clone());
+            }
+            if let Some(w) = info.wanted_values() {
+                wanted.insert(info.syllable.clone(), w);
+            }
+        }
+
+        cfg.set_phonology(self.phonology);
+        cfg.set_adjacency_rules(self.adjacency);
+        cfg.set_syllables(self.syllables.into_iter().map(|s| s.syllable).collect());
+        cfg.set_syllable_pos(positions);
+        cfg.set_romanization(romanization);
+        cfg.set_wanted(wanted);
+        cfg.set_tuning(self.tuning);
+        cfg.set_database(self.words);
+    }
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Separates fields of an entry in the word database
+const FIELD_SEPARATOR: char = '\t';
+/// Separates tags inside the tags field
+const TAG_SEPARATOR: char = ',';
+
+/// A word of the language together with what is known about it
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct LexiconEntry {
+    pub word: String,
+    /// Meaning of the word
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gloss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part_of_speech: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Date the word was added, YYYY-MM-DD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl LexiconEntry {
+    /// Entry without any metadata
+    pub fn new(word: &str) -> LexiconEntry {
+        LexiconEntry {
+            word: word.to_string(),
+            ..LexiconEntry::default()
+        }
+    }
+
+    /// Entry created today
+    pub fn created_today(word: &str) -> LexiconEntry {
+        LexiconEntry {
+            created: Some(chrono::Local::now().format("%Y-%m-%d").to_string()),
+            ..LexiconEntry::new(word)
+        }
+    }
+
+    /// Parses a line of the word database. Fields are separated by tabs in the order
+    /// word, gloss, part of speech, tags, created, notes. Missing fields are empty,
+    /// so a line with a bare word is an entry without metadata
+    pub fn from_line(line: &str) -> LexiconEntry {
+        let mut fields = line.split(FIELD_SEPARATOR);
+
+        LexiconEntry {
+            word: fields.next().unwrap_or_default().to_string(),
+            gloss: non_empty(fields.next()),
+            part_of_speech: non_empty(fields.next()),
+            tags: fields
+                .next()
+                .unwrap_or_default()
+                .split(TAG_SEPARATOR)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+            created: non_empty(fields.next()),
+            notes: non_empty(fields.next()),
+        }
+    }
+
+    /// Line of the word database, see from_line. Entries without metadata are just the word
+    pub fn to_line(&self) -> String {
+        let mut fields = vec![
+            self.word.clone(),
+            self.gloss.clone().unwrap_or_default(),
+            self.part_of_speech.clone().unwrap_or_default(),
+            self.tags.join(&TAG_SEPARATOR.to_string()),
+            self.created.clone().unwrap_or_default(),
+            self.notes.clone().unwrap_or_default(),
+        ];
+
+        while fields.len() > 1 && fields.last().unwrap().is_empty() {
+            fields.pop(); // Trailing empty fields are not written
+        }
+
+        fields.join(&FIELD_SEPARATOR.to_string())
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+impl fmt::Display for LexiconEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.word)?;
+
+        if let Some(pos) = &self.part_of_speech {
+            write!(f, " ({})", pos)?;
+        }
+        if let Some(gloss) = &self.gloss {
+            write!(f, " \"{}\"", gloss)?;
+        }
+        if !self.tags.is_empty() {
+            write!(f, " [{}]", self.tags.join(", "))?;
+        }
+        if let Some(created) = &self.created {
+            write!(f, " added {}", created)?;
+        }
+        if let Some(notes) = &self.notes {
+            write!(f, " - {}", notes)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Field values can't contain the separators used in the database file
+pub fn clean_field(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c == FIELD_SEPARATOR || c == '\n' || c == '\r' {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn non_empty(field: Option<&str>) -> Option<String> {
+    match field {
+        Some(f) if !f.is_empty() => Some(f.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,136 @@
+use crate::languagepack::LanguagePack;
+use crate::wanted::{Wanted, WordPosition};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Wanted values may be off from adding up to 1 by this much
+const WANTED_SUM_TOLERANCE: f64 = 1e-6;
+
+/// Inconsistency found in config of a language
+pub struct LintIssue {
+    /// Name of the file the issue is in
+    pub file: String,
+    /// Line the issue is on, counted from 1. None if it concerns the whole file
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl LintIssue {
+    pub fn new(file: &str, line: Option<usize>, message: String) -> LintIssue {
+        LintIssue {
+            file: file.to_string(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// Tells which wanted values of all syllables don't add up to 1. Values of a position
+/// are only checked if some syllable has one of its own there
+pub fn wanted_sum_issues<'a, I>(values: I) -> Vec<String>
+where
+    I: Iterator<Item = &'a Wanted> + Clone,
+{
+    let mut issues = Vec::new();
+    if values.clone().next().is_none() {
+        return issues;
+    }
+
+    let overall: f64 = values.clone().map(|w| w.overall).sum();
+    if (overall - 1.0).abs() > WANTED_SUM_TOLERANCE {
+        issues.push(format!("values add up to {} instead of 1", overall));
+    }
+
+    for position in WordPosition::ALL.iter() {
+        if values.clone().all(|w| w.only_at(*position).is_none()) {
+            continue;
+        }
+
+        let sum: f64 = values.clone().map(|w| w.at(*position)).sum();
+        if (sum - 1.0).abs() > WANTED_SUM_TOLERANCE {
+            issues.push(format!(
+                "{} values add up to {} instead of 1",
+                position, sum
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Checks a language stored as a single document @file. Such documents have no lines
+/// to point at, issues name the syllable or word instead
+pub fn lint_pack(file: &str, pack: &LanguagePack) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |message: String| issues.push(LintIssue::new(file, None, message));
+
+    let mut syllables = HashSet::new();
+    for info in &pack.syllables {
+        if info.syllable.trim().is_empty() {
+            issue("empty syllable".to_string());
+            continue;
+        }
+        if !syllables.insert(&info.syllable) {
+            issue(format!(
+                "syllable {} is listed more than once",
+                info.syllable
+            ));
+        }
+        if info.position.is_none() {
+            issue(format!("syllable {} has no position", info.syllable));
+        }
+        if info.romanization.is_none() {
+            issue(format!("syllable {} has no romanization", info.syllable));
+        }
+        if info.wanted.is_none() {
+            issue(format!("syllable {} has no wanted value", info.syllable));
+        }
+        for wanted in [
+            info.wanted,
+            info.wanted_start,
+            info.wanted_middle,
+            info.wanted_end,
+        ]
+        .iter()
+        .flatten()
+        {
+            if !wanted.is_finite() || *wanted < 0.0 {
+                issue(format!(
+                    "syllable {} has wanted value {} which is not a number which is not negative",
+                    info.syllable, wanted
+                ));
+            }
+        }
+    }
+
+    let wanted: Vec<Wanted> = pack
+        .syllables
+        .iter()
+        .filter_map(|s| s.wanted_values())
+        .collect();
+    for message in wanted_sum_issues(wanted.iter()) {
+        issue(format!("wanted {}", message));
+    }
+
+    if let Err(message) = pack.tuning.check() {
+        issue(message);
+    }
+
+    let mut words = HashSet::new();
+    for entry in &pack.words {
+        if !words.insert(&entry.word) {
+            issue(format!("word {} is listed more than once", entry.word));
+        }
+    }
+
+    issues
+}
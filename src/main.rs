@@ -1,30 +1,58 @@
 #![feature(nll)]
-#![feature(vec_remove_item)]
 
 extern crate clap;
 extern crate clipboard;
 extern crate rand;
 
+mod backend;
 mod calculatedrandom;
 mod config;
 mod configcmd;
+mod convertcmd;
 mod dbcmd;
 mod error;
+mod exportcmd;
+mod filesafety;
 mod filesystemconfig;
 mod gencmd;
+mod importcmd;
+mod initcmd;
+mod journal;
+mod langcmd;
+mod languagepack;
+mod lexicon;
+mod lint;
+mod markovrandom;
+mod phonology;
+mod phonotactics;
+mod profile;
+mod proposals;
 mod rangen;
 mod realrandom;
+mod sqliteconfig;
+mod starter;
 mod syllables;
+mod tomlconfig;
+mod tuning;
+mod wanted;
+mod weightedrandom;
 
 use crate::config::LangConfig;
 use crate::configcmd::ConfigCmd;
+use crate::convertcmd::ConvertCmd;
 use crate::dbcmd::DatabaseCmd;
 use crate::error::LangErr;
+use crate::exportcmd::ExportCmd;
+use crate::filesafety::DirLock;
 use crate::filesystemconfig::FileSystemConfig;
 use crate::gencmd::GenerateCmd;
+use crate::importcmd::ImportCmd;
+use crate::initcmd::InitCmd;
+use crate::langcmd::LangCmd;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use std::borrow::BorrowMut;
 use std::collections::HashMap;
+use std::fs;
 
 type Result<T> = std::result::Result<T, crate::error::LangErr>;
 
@@ -33,6 +61,29 @@ fn prepare_app<'a, 'b>() -> App<'a, 'b> {
         .version("0.3")
         .author("Lukx")
         .about("Generates stuff for my language")
+        .arg(
+            Arg::with_name("lang")
+                .long("lang")
+                .takes_value(true)
+                .global(true)
+                .help("Language to work with instead of the default one"),
+        )
+        .arg(
+            Arg::with_name("config-dir")
+                .long("config-dir")
+                .takes_value(true)
+                .global(true)
+                .conflicts_with("lang")
+                .help("Directory with config files of the language, overrides LANGGEN_HOME"),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .global(true)
+                .possible_values(&backend::BACKEND_NAMES)
+                .help("How the language is stored, found out from its files if not set"),
+        )
         .subcommand(
             SubCommand::with_name("gen")
                 .about("Generates a word or words")
@@ -48,7 +99,31 @@ fn prepare_app<'a, 'b>() -> App<'a, 'b> {
                     Arg::with_name("realrandom")
                         .long("realrandom")
                         .takes_value(false)
-                        .help("Uses real RNG for all randomness"),
+                        .help("Uses real RNG for all randomness")
+                        .conflicts_with("engine"),
+                )
+                .arg(
+                    Arg::with_name("engine")
+                        .short("e")
+                        .long("engine")
+                        .possible_values(&["calculated", "real", "markov", "weighted"])
+                        .default_value("calculated")
+                        .takes_value(true)
+                        .help("Engine used to generate words"),
+                )
+                .arg(
+                    Arg::with_name("order")
+                        .long("order")
+                        .default_value("2")
+                        .takes_value(true)
+                        .help("How many previous syllables the markov engine looks at"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .short("s")
+                        .long("seed")
+                        .takes_value(true)
+                        .help("Seed for the random generator, same seed generates same words"),
                 )
                 .arg(
                     Arg::with_name("min")
@@ -91,8 +166,55 @@ fn prepare_app<'a, 'b>() -> App<'a, 'b> {
                 .arg(
                     Arg::with_name("db")
                         .short("db")
+                        .long("db")
                         .help("Words will be added to the database")
                         .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("unique")
+                        .long("unique")
+                        .takes_value(false)
+                        .help("Words will differ from each other and from the database, default with --db"),
+                )
+                .arg(
+                    Arg::with_name("no-unique")
+                        .long("no-unique")
+                        .takes_value(false)
+                        .conflicts_with("unique")
+                        .help("Words may repeat even with --db"),
+                )
+                .arg(
+                    Arg::with_name("history")
+                        .long("history")
+                        .possible_values(&["on", "off", "reset"])
+                        .default_value("off")
+                        .takes_value(true)
+                        .help("Remembers syllables of words not added to the database, the calculated engine proposes them less. Reset forgets them first. Other engines don't use it"),
+                )
+                .arg(
+                    Arg::with_name("top-fraction")
+                        .long("top-fraction")
+                        .takes_value(true)
+                        .help("Share of the best syllables the calculated engine picks from"),
+                )
+                .arg(
+                    Arg::with_name("metric")
+                        .long("metric")
+                        .takes_value(true)
+                        .possible_values(&tuning::METRIC_NAMES)
+                        .help("How the calculated engine measures distance from wanted values"),
+                )
+                .arg(
+                    Arg::with_name("temperature")
+                        .long("temperature")
+                        .takes_value(true)
+                        .help("Picks better syllables more often, the lower the more, 'off' picks evenly"),
+                )
+                .arg(
+                    Arg::with_name("min-distance")
+                        .long("min-distance")
+                        .takes_value(true)
+                        .help("Discards words at most this many syllable edits away from a word in the database"),
                 ),
         )
         .subcommand(
@@ -106,6 +228,14 @@ fn prepare_app<'a, 'b>() -> App<'a, 'b> {
                         .takes_value(true)
                         .multiple(true),
                 )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .help("Adds words even if they break syllable rules")
+                        .requires("add")
+                        .takes_value(false),
+                )
                 .arg(
                     Arg::with_name("del")
                         .short("d")
@@ -120,6 +250,125 @@ fn prepare_app<'a, 'b>() -> App<'a, 'b> {
                         .long("list")
                         .help("List all words in the database")
                         .takes_value(false),
+                )
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Adds word or words to the database")
+                        .arg(Arg::with_name("words").required(true).multiple(true))
+                        .arg(
+                            Arg::with_name("gloss")
+                                .short("g")
+                                .long("gloss")
+                                .takes_value(true)
+                                .help("Meaning of the words"),
+                        )
+                        .arg(
+                            Arg::with_name("pos")
+                                .short("p")
+                                .long("pos")
+                                .takes_value(true)
+                                .help("Part of speech of the words"),
+                        )
+                        .arg(
+                            Arg::with_name("tag")
+                                .short("t")
+                                .long("tag")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help("Tag of the words, may be used more times"),
+                        )
+                        .arg(
+                            Arg::with_name("notes")
+                                .short("n")
+                                .long("notes")
+                                .takes_value(true)
+                                .help("Notes about the words"),
+                        )
+                        .arg(
+                            Arg::with_name("force")
+                                .short("f")
+                                .long("force")
+                                .takes_value(false)
+                                .help("Adds words even if they break syllable rules"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("del")
+                        .about("Deletes word or words from the database")
+                        .arg(Arg::with_name("words").required(true).multiple(true)),
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists words in the database")
+                        .arg(
+                            Arg::with_name("gloss")
+                                .short("g")
+                                .long("gloss")
+                                .takes_value(true)
+                                .help("Only words whose meaning contains this"),
+                        )
+                        .arg(
+                            Arg::with_name("pos")
+                                .short("p")
+                                .long("pos")
+                                .takes_value(true)
+                                .help("Only words with this part of speech"),
+                        )
+                        .arg(
+                            Arg::with_name("tag")
+                                .short("t")
+                                .long("tag")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help("Only words with this tag, may be used more times"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("check")
+                        .about("Lists words in the database which break syllable rules"),
+                )
+                .subcommand(
+                    SubCommand::with_name("history")
+                        .about("Lists changes of the database, oldest first")
+                        .arg(
+                            Arg::with_name("count")
+                                .short("c")
+                                .long("count")
+                                .takes_value(true)
+                                .help("Amount of latest changes to list, all if not set"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("undo")
+                        .about("Reverts latest changes of the database")
+                        .arg(
+                            Arg::with_name("n")
+                                .default_value("1")
+                                .help("Amount of changes to revert"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("similar")
+                        .about("Lists words in the database which differ from a word by few syllables")
+                        .arg(Arg::with_name("word").required(true))
+                        .arg(
+                            Arg::with_name("max")
+                                .short("m")
+                                .long("max")
+                                .default_value("1")
+                                .help("Most syllable edits a listed word may be away"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("segment")
+                        .about("Shows the ways words can be split into syllables")
+                        .arg(
+                            Arg::with_name("words")
+                                .help("Words to split, every word in the database if not set")
+                                .multiple(true),
+                        ),
                 ),
         )
         .subcommand(
@@ -137,6 +386,147 @@ fn prepare_app<'a, 'b>() -> App<'a, 'b> {
                         .short("r")
                         .long("real")
                         .takes_value(false),
+                )
+                .subcommand(
+                    SubCommand::with_name("lint")
+                        .about("Reports inconsistencies between config files"),
+                )
+                .subcommand(
+                    SubCommand::with_name("tuning")
+                        .about("Shows or sets defaults of the calculated engine")
+                        .arg(
+                            Arg::with_name("top-fraction")
+                                .long("top-fraction")
+                                .takes_value(true)
+                                .help("Share of the best syllables to pick from"),
+                        )
+                        .arg(
+                            Arg::with_name("metric")
+                                .long("metric")
+                                .takes_value(true)
+                                .possible_values(&tuning::METRIC_NAMES)
+                                .help("How distance from wanted values is measured"),
+                        )
+                        .arg(
+                            Arg::with_name("temperature")
+                                .long("temperature")
+                                .takes_value(true)
+                                .help("Softmax temperature of the pick, 'off' picks evenly"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("syllables")
+                        .about("Lists syllables")
+                        .arg(
+                            Arg::with_name("generate")
+                                .short("g")
+                                .long("generate")
+                                .takes_value(false)
+                                .help("Replaces syllables with ones generated from phonology"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lang")
+                .about("Language management")
+                .subcommand(SubCommand::with_name("list").about("Lists all languages"))
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("Creates a new language with empty config files")
+                        .arg(Arg::with_name("name").required(true)),
+                )
+                .subcommand(
+                    SubCommand::with_name("switch")
+                        .about("Makes a language the default one")
+                        .arg(Arg::with_name("name").required(true)),
+                )
+                .subcommand(
+                    SubCommand::with_name("delete")
+                        .about("Deletes a language with all of its words")
+                        .arg(Arg::with_name("name").required(true))
+                        .arg(
+                            Arg::with_name("yes")
+                                .long("yes")
+                                .takes_value(false)
+                                .help("Confirms the language should be deleted"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("Creates config files of a new language from a template")
+                .arg(
+                    Arg::with_name("template")
+                        .short("t")
+                        .long("template")
+                        .possible_values(&starter::TEMPLATE_NAMES)
+                        .default_value("cv")
+                        .takes_value(true)
+                        .help("Template the language starts from"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .takes_value(false)
+                        .help("Overwrites existing config files, words included"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("Stores the language with another backend")
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .required(true)
+                        .possible_values(&backend::BACKEND_NAMES)
+                        .takes_value(true)
+                        .help("Backend to store the language with"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Directory to store the converted language in instead of its own"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .takes_value(false)
+                        .help("Overwrites config files already in the output directory"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Writes the whole language into one document")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .possible_values(&["json"])
+                        .default_value("json")
+                        .takes_value(true)
+                        .help("Format of the document"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .help("File to write the document to, printed if not set"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Replaces the language with one from an exported document")
+                .arg(Arg::with_name("file").required(true))
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .takes_value(false)
+                        .help("Overwrites existing config files, words included"),
                 ),
         )
 }
@@ -147,6 +537,11 @@ fn prepare_callers() -> TakeAppArgManager {
         Box::new(GenerateCmd::new()),
         Box::new(DatabaseCmd::new()),
         Box::new(ConfigCmd::new()),
+        Box::new(LangCmd::new()),
+        Box::new(InitCmd::new()),
+        Box::new(ConvertCmd::new()),
+        Box::new(ExportCmd::new()),
+        Box::new(ImportCmd::new()),
     ];
 
     let mut subcommands = HashMap::new();
@@ -158,11 +553,57 @@ fn prepare_callers() -> TakeAppArgManager {
     TakeAppArgManager::new(subcommands)
 }
 
-/// Loads language config
-fn prepare_lang_cfg() -> Box<dyn LangConfig> {
-    let mut cfg = FileSystemConfig::default();
-    cfg.load().unwrap();
-    Box::new(cfg)
+/// Prepares config of the language chosen in @matches, it is not loaded yet
+fn prepare_lang_cfg(matches: &ArgMatches) -> Result<Box<dyn LangConfig>> {
+    let dir = profile::resolve_dir(
+        global_value_of(matches, "config-dir"),
+        global_value_of(matches, "lang"),
+    )?;
+    Ok(backend::open(&dir, global_value_of(matches, "backend")))
+}
+
+/// Value of a global arg, which may be set before or after the subcommand
+fn global_value_of<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    let subcommand_value = matches
+        .subcommand_name()
+        .and_then(|sc| matches.subcommand_matches(sc))
+        .and_then(|sub| sub.value_of(name));
+
+    subcommand_value.or_else(|| matches.value_of(name))
+}
+
+/// Runs subcommand @sc, preparing language config the way it needs it
+fn exec_subcommand(callers: &mut TakeAppArgManager, matches: &ArgMatches, sc: &str) -> Result<()> {
+    let arguments = matches.subcommand_matches(sc).unwrap();
+    let caller = callers.get(sc);
+
+    let mut lock = None;
+    let lang_cfg = match caller.config_use(arguments) {
+        ConfigUse::Load => {
+            let mut cfg = prepare_lang_cfg(matches)?;
+            cfg.load()?;
+            cfg
+        }
+        ConfigUse::LoadLocked => {
+            let mut cfg = prepare_lang_cfg(matches)?;
+            lock = Some(DirLock::acquire(cfg.dir())?);
+            cfg.load()?;
+            cfg
+        }
+        ConfigUse::Unloaded => prepare_lang_cfg(matches)?,
+        ConfigUse::UnloadedLocked => {
+            let cfg = prepare_lang_cfg(matches)?;
+            fs::create_dir_all(cfg.dir())?; // New languages have no directory to lock yet
+            lock = Some(DirLock::acquire(cfg.dir())?);
+            cfg
+        }
+        ConfigUse::Nothing => Box::new(FileSystemConfig::in_dir(&profile::app_root_dir())),
+    };
+
+    let result = caller.do_exec(arguments, lang_cfg);
+    drop(lock); // Held until changes are written
+
+    result
 }
 
 /// Called when err is encountered
@@ -174,9 +615,71 @@ fn handle_err(err: LangErr) {
         InvalidSyllable(syllable) => eprintln!("Invalid syllable {}", syllable),
         Io(e) => eprintln!("Read or write error: {:?}", e),
         FileEmpty(e) => eprintln!("File {} is empty", e),
+        MissingFile(path) => eprintln!(
+            "{} does not exist, config files can be created with init",
+            path
+        ),
         InvalidSyllablePosition(syllable, pos) => {
             eprintln!("Syllable {} found in invalid position {}", syllable, pos)
         }
+        InvalidWord(word, reason) => eprintln!("Invalid word {}: {}", word, reason),
+        InvalidTemplate(template, reason) => {
+            eprintln!("Invalid syllable template {}: {}", template, reason)
+        }
+        InvalidLanguageName(name) => eprintln!("{} can't be used as a language name", name),
+        LanguageExists(name) => eprintln!("Language {} already exists", name),
+        UnknownLanguage(name) => eprintln!("Language {} does not exist", name),
+        ConfigExists(dir) => eprintln!(
+            "{} already holds a language, overwrite it with --force",
+            dir
+        ),
+        ConfigChanged(path) => eprintln!(
+            "{} was changed by another program since it was read, nothing was written",
+            path
+        ),
+        WordSpaceExhausted {
+            wanted,
+            found,
+            min_len,
+            max_len,
+        } => eprintln!(
+            "Only {} of {} distinct new words with {} to {} syllables could be generated, \
+             try a wider length range or another engine",
+            found, wanted, min_len, max_len
+        ),
+        Sqlite(e) => eprintln!("Database error: {}", e),
+        InvalidTuning(reason) => eprintln!("Invalid tuning: {}", reason),
+        HistoryUnused(engine) => eprintln!(
+            "The {} engine does not use proposal history, --history works only with the calculated engine",
+            engine
+        ),
+        UnlistedEntries(entries) => eprintln!(
+            "Syllables of these entries are not listed, they would be lost: {}. \
+             List the syllables or remove the entries, config lint shows where they are",
+            entries.join(", ")
+        ),
+        Serialization(reason) => eprintln!("Config could not be written: {}", reason),
+        ConfigParse {
+            file,
+            line: Some(line),
+            message,
+        } => eprintln!("{}:{}: {}", file, line, message),
+        ConfigParse {
+            file,
+            line: None,
+            message,
+        } => eprintln!("{}: {}", file, message),
+        NoValidSyllable { pos, len, rejected } if rejected.is_empty() => eprintln!(
+            "No syllable can be placed at position {} of a word with {} syllables",
+            pos + 1,
+            len
+        ),
+        NoValidSyllable { pos, len, rejected } => eprintln!(
+            "No syllable can be placed at position {} of a word with {} syllables, rejected: {}",
+            pos + 1,
+            len,
+            rejected.join(", ")
+        ),
     }
 }
 
@@ -185,8 +688,6 @@ fn are_launch_args_set() -> bool {
 }
 
 fn main() {
-    let lang_cfg = prepare_lang_cfg();
-
     let mut app = prepare_app();
 
     if !are_launch_args_set() {
@@ -200,9 +701,7 @@ fn main() {
 
     let result = match matches.subcommand_name() {
         // Check what subcommand was set
-        Some(sc) => callers
-            .get(sc)
-            .do_exec(matches.subcommand_matches(sc).unwrap(), lang_cfg),
+        Some(sc) => exec_subcommand(&mut callers, &matches, sc),
         None => unreachable!(), // App will prevent unknown subcommands to reach this point
     };
 
@@ -234,5 +733,22 @@ impl TakeAppArgManager {
 /// Implement this for every subcommand and put the implementer into prepare_callers()
 pub trait TakeAppArg {
     fn subcommand(&self) -> &str;
+    fn config_use(&self, _arguments: &ArgMatches) -> ConfigUse {
+        ConfigUse::Load
+    }
     fn do_exec(&mut self, arguments: &ArgMatches, cfg: Box<dyn LangConfig>) -> Result<()>;
 }
+
+/// How a subcommand uses config of the chosen language
+pub enum ConfigUse {
+    /// Config is loaded before the subcommand runs
+    Load,
+    /// Same as Load, but no other process may change the language until the subcommand ends
+    LoadLocked,
+    /// Config of the chosen language is given but not loaded
+    Unloaded,
+    /// Same as Unloaded, but no other process may change the language until the subcommand ends
+    UnloadedLocked,
+    /// Subcommand does not work with any language in particular
+    Nothing,
+}
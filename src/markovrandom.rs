@@ -0,0 +1,147 @@
+use crate::config::LangConfig;
+use crate::rangen::{EngineRng, RandomEngine};
+use crate::{rangen, syllables, Result};
+use rand::Rng;
+use std::collections::{BTreeMap, HashMap};
+
+/// Marks syllables before the start of a word inside a context
+const WORD_BOUNDARY: &str = "";
+
+/// Generates words from a syllable Markov chain learned from the word database.
+/// Falls back to shorter contexts when the learned one has no valid continuation.
+/// Returns error if no syllable fits some position of a word
+pub struct MarkovRandom {
+    order: usize,
+    /// (Previous syllables -> (Next syllable -> count)) for every context length up to order
+    transitions: HashMap<Vec<String>, BTreeMap<String, u32>>,
+    rng: EngineRng,
+}
+
+impl MarkovRandom {
+    /// Trains a model which looks at up to @order previous syllables
+    pub fn with_order(
+        cfg: &dyn LangConfig,
+        order: usize,
+        seed: Option<u64>,
+    ) -> Result<MarkovRandom> {
+        let mut transitions: HashMap<Vec<String>, BTreeMap<String, u32>> = HashMap::new();
+
+        for entry in cfg.database()? {
+            let syllables = syllables::split_into_syllables(&entry.word, cfg)?;
+
+            for (pos, syllable) in syllables.iter().enumerate() {
+                for context_len in 0..=order {
+                    let context = context_of(&syllables[..pos], context_len);
+                    transitions
+                        .entry(context)
+                        .or_default()
+                        .entry(syllable.to_string())
+                        .and_modify(|e| *e += 1)
+                        .or_insert(1);
+                }
+            }
+        }
+
+        Ok(MarkovRandom {
+            order,
+            transitions,
+            rng: rangen::engine_rng(seed),
+        })
+    }
+
+    fn pull_syllable(
+        &mut self,
+        previous: &[String],
+        len: usize,
+        cfg: &dyn LangConfig,
+    ) -> Result<String> {
+        // Longest known context first, shorter ones only if it leads nowhere
+        for context_len in (0..=self.order).rev() {
+            let context = context_of(previous, context_len);
+
+            if let Some(next) = self.transitions.get(&context) {
+                let candidates: Vec<(&String, u32)> = next
+                    .iter()
+                    .filter(|(s, _)| syllables::is_syllable_valid_after(s, previous, len, cfg))
+                    .map(|(s, count)| (s, *count))
+                    .collect();
+
+                if !candidates.is_empty() {
+                    let total: u32 = candidates.iter().map(|(_, count)| count).sum();
+                    let mut pick = self.rng.gen_range(0, total);
+
+                    for (syllable, count) in candidates {
+                        if pick < count {
+                            return Ok(syllable.to_string());
+                        }
+                        pick -= count;
+                    }
+                }
+            }
+        }
+
+        // Nothing learned fits here, any valid syllable will do
+        let valid: Vec<&String> = cfg
+            .syllables()
+            .iter()
+            .filter(|s| syllables::is_syllable_valid_after(s, previous, len, cfg))
+            .collect();
+
+        if valid.is_empty() {
+            let rejected = rangen::invalid_syllables(previous, len, cfg);
+            return Err(rangen::no_valid_syllable(previous.len(), len, rejected));
+        }
+
+        Ok(valid[rangen::random_index(&mut self.rng, valid.len())].to_string())
+    }
+
+    fn create_word(&mut self, len: usize, cfg: &dyn LangConfig) -> Result<String> {
+        let mut syllables = Vec::with_capacity(len);
+
+        for _pos in 0..len {
+            let syllable = self.pull_syllable(&syllables, len, cfg)?;
+            syllables.push(syllable);
+        }
+
+        Ok(syllables.concat())
+    }
+}
+
+/// Last @len syllables of @previous, padded with word boundaries at the start of a word
+fn context_of(previous: &[String], len: usize) -> Vec<String> {
+    let mut context = Vec::with_capacity(len);
+
+    for _ in previous.len()..len {
+        context.push(WORD_BOUNDARY.to_string());
+    }
+
+    let skip = previous.len().saturating_sub(len);
+    context.extend(previous[skip..].iter().cloned());
+
+    context
+}
+
+impl RandomEngine for MarkovRandom {
+    fn create_words(
+        &mut self,
+        min_len: u32,
+        max_len: u32,
+        count: u32,
+        cfg: &dyn LangConfig,
+    ) -> Result<Vec<String>> {
+        let mut result = Vec::with_capacity(count as usize);
+
+        for _nth_word in 0..count {
+            let chosen_length = self.rng.gen_range(min_len, max_len + 1) as usize;
+            let word = rangen::retry_word(|| self.create_word(chosen_length, cfg))?;
+
+            result.push(word);
+        }
+
+        Ok(result)
+    }
+
+    fn with_config(cfg: &dyn LangConfig, seed: Option<u64>) -> Result<Self> {
+        MarkovRandom::with_order(cfg, 2, seed)
+    }
+}
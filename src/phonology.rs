@@ -0,0 +1,145 @@
+use crate::error::LangErr::InvalidTemplate;
+use crate::Result;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Phoneme classes of a language and syllable templates built from them
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Phonology {
+    /// Templates such as (C)V(N), parentheses mark optional parts
+    #[serde(default)]
+    pub templates: Vec<String>,
+    /// (Class symbol -> phonemes), e.g. C -> p, t, k
+    #[serde(default, serialize_with = "serialize_classes")]
+    pub classes: BTreeMap<char, Vec<String>>,
+}
+
+/// Some formats only take strings as keys, so class symbols are written as strings
+fn serialize_classes<S: Serializer>(
+    classes: &BTreeMap<char, Vec<String>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        classes
+            .iter()
+            .map(|(class, phonemes)| (class.to_string(), phonemes)),
+    )
+}
+
+/// Part of a parsed syllable template
+enum TemplatePart {
+    Class(char),
+    Literal(char),
+    Optional(Vec<TemplatePart>),
+}
+
+impl Phonology {
+    /// Expands every template into syllables. Syllables produced by more than one template
+    /// are only listed once, in the order they were first produced
+    pub fn generate_syllables(&self) -> Result<Vec<String>> {
+        if self.templates.is_empty() {
+            return Err(InvalidTemplate(
+                String::new(),
+                "no templates are defined".to_string(),
+            ));
+        }
+
+        let mut result: Vec<String> = Vec::new();
+
+        for template in &self.templates {
+            for syllable in self.expand_template(template)? {
+                if !result.contains(&syllable) {
+                    result.push(syllable);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Expands a single template into every syllable it describes
+    pub fn expand_template(&self, template: &str) -> Result<Vec<String>> {
+        let mut chars = template.chars().filter(|c| !c.is_whitespace());
+        let parts = self.parse_parts(template, &mut chars, false)?;
+
+        let syllables: Vec<String> = self
+            .expand_parts(&parts)
+            .into_iter()
+            .filter(|s| !s.is_empty()) // Template made only of optional parts
+            .collect();
+
+        if syllables.is_empty() {
+            return Err(InvalidTemplate(
+                template.to_string(),
+                "template does not produce any syllable".to_string(),
+            ));
+        }
+
+        Ok(syllables)
+    }
+
+    fn parse_parts(
+        &self,
+        template: &str,
+        chars: &mut dyn Iterator<Item = char>,
+        in_group: bool,
+    ) -> Result<Vec<TemplatePart>> {
+        let mut parts = Vec::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '(' => parts.push(TemplatePart::Optional(
+                    self.parse_parts(template, chars, true)?,
+                )),
+                ')' if in_group => return Ok(parts),
+                ')' => {
+                    return Err(InvalidTemplate(
+                        template.to_string(),
+                        "unexpected ')'".to_string(),
+                    ))
+                }
+                c if self.classes.contains_key(&c) => parts.push(TemplatePart::Class(c)),
+                c if c.is_uppercase() => {
+                    return Err(InvalidTemplate(
+                        template.to_string(),
+                        format!("unknown phoneme class {}", c),
+                    ))
+                }
+                c => parts.push(TemplatePart::Literal(c)),
+            }
+        }
+
+        if in_group {
+            return Err(InvalidTemplate(
+                template.to_string(),
+                "missing ')'".to_string(),
+            ));
+        }
+
+        Ok(parts)
+    }
+
+    fn expand_parts(&self, parts: &[TemplatePart]) -> Vec<String> {
+        let mut result = vec![String::new()];
+
+        for part in parts {
+            let options: Vec<String> = match part {
+                TemplatePart::Class(c) => self.classes[c].clone(),
+                TemplatePart::Literal(c) => vec![c.to_string()],
+                TemplatePart::Optional(inner) => {
+                    let mut options = vec![String::new()];
+                    options.extend(self.expand_parts(inner));
+                    options
+                }
+            };
+
+            // Every syllable so far continued with every option
+            result = result
+                .iter()
+                .flat_map(|start| options.iter().map(move |o| format!("{}{}", start, o)))
+                .collect();
+        }
+
+        result
+    }
+}
@@ -0,0 +1,120 @@
+use crate::phonology::Phonology;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Restricts which syllable may follow another one inside a word.
+/// Patterns are matched against whole syllables, `*` matches anything and
+/// a phoneme class symbol matches any phoneme of that class, e.g. `*N` is
+/// a syllable ending with a nasal
+#[derive(Clone, PartialEq, Debug)]
+pub enum AdjacencyRule {
+    /// Syllable matching the second pattern can't follow one matching the first
+    Forbid(String, String),
+    /// Syllable following one matching the first pattern must match the second
+    Require(String, String),
+    /// The same syllable can't follow itself
+    ForbidRepeat,
+}
+
+impl AdjacencyRule {
+    /// Parses a line of the adjacency rules file, error tells what is wrong with the line
+    pub fn from_line(line: &str) -> std::result::Result<AdjacencyRule, String> {
+        let split: Vec<&str> = line.splitn(3, ':').collect();
+
+        match split.as_slice() {
+            ["forbid", "repeat"] => Ok(AdjacencyRule::ForbidRepeat),
+            ["forbid", prev, next] => Ok(AdjacencyRule::Forbid(prev.to_string(), next.to_string())),
+            ["require", prev, next] => {
+                Ok(AdjacencyRule::Require(prev.to_string(), next.to_string()))
+            }
+            _ => Err(format!(
+                "expected 'forbid:repeat', 'forbid:A:B' or 'require:A:B' instead of '{}'",
+                line
+            )),
+        }
+    }
+
+    /// Checks whether @next may directly follow @previous
+    pub fn allows(&self, previous: &str, next: &str, phonology: &Phonology) -> bool {
+        match self {
+            AdjacencyRule::Forbid(prev_pattern, next_pattern) => {
+                !(matches_pattern(prev_pattern, previous, phonology)
+                    && matches_pattern(next_pattern, next, phonology))
+            }
+            AdjacencyRule::Require(prev_pattern, next_pattern) => {
+                !matches_pattern(prev_pattern, previous, phonology)
+                    || matches_pattern(next_pattern, next, phonology)
+            }
+            AdjacencyRule::ForbidRepeat => previous != next,
+        }
+    }
+}
+
+impl fmt::Display for AdjacencyRule {
+    /// Same format as lines of the adjacency rules file
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdjacencyRule::Forbid(prev, next) => write!(f, "forbid:{}:{}", prev, next),
+            AdjacencyRule::Require(prev, next) => write!(f, "require:{}:{}", prev, next),
+            AdjacencyRule::ForbidRepeat => write!(f, "forbid:repeat"),
+        }
+    }
+}
+
+// Rules are stored the same way as in the adjacency rules file
+impl Serialize for AdjacencyRule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AdjacencyRule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let line = String::deserialize(deserializer)?;
+        AdjacencyRule::from_line(&line).map_err(de::Error::custom)
+    }
+}
+
+/// Checks whether @next may directly follow @previous under every rule
+pub fn may_follow(
+    previous: &str,
+    next: &str,
+    rules: &[AdjacencyRule],
+    phonology: &Phonology,
+) -> bool {
+    rules
+        .iter()
+        .all(|rule| rule.allows(previous, next, phonology))
+}
+
+/// Matches a whole syllable against a pattern
+pub fn matches_pattern(pattern: &str, syllable: &str, phonology: &Phonology) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    matches_from(&pattern, syllable, phonology)
+}
+
+fn matches_from(pattern: &[char], rest: &str, phonology: &Phonology) -> bool {
+    let (first, pattern_rest) = match pattern.split_first() {
+        Some(split) => split,
+        None => return rest.is_empty(), // Whole syllable must be matched
+    };
+
+    if *first == '*' {
+        // Try to skip every possible amount of chars
+        return rest
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(rest.len()))
+            .any(|i| matches_from(pattern_rest, &rest[i..], phonology));
+    }
+
+    if let Some(phonemes) = phonology.classes.get(first) {
+        return phonemes.iter().any(|phoneme| {
+            !phoneme.is_empty()
+                && rest.starts_with(phoneme.as_str())
+                && matches_from(pattern_rest, &rest[phoneme.len()..], phonology)
+        });
+    }
+
+    rest.starts_with(*first) && matches_from(pattern_rest, &rest[first.len_utf8()..], phonology)
+}
@@ -0,0 +1,151 @@
+use crate::error::LangErr::{InvalidLanguageName, LanguageExists, UnknownLanguage};
+use crate::filesafety::DirLock;
+use crate::Result;
+use app_dirs::*;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const APP_INFO: AppInfo = AppInfo {
+    name: "LangGen",
+    author: "LukxNet",
+};
+
+/// Directory inside app root holding one directory per language
+const LANGS_DIR_NAME: &str = "langs";
+/// Environment variable with a directory holding config files of a language
+const HOME_VAR_NAME: &str = "LANGGEN_HOME";
+/// File inside app root remembering which language is used when none is chosen
+const DEFAULT_LANG_NAME: &str = "DefaultLang.txt";
+
+/// Root of all app config. Used as the language directory when no language exists,
+/// which is where the config files were before languages could be named
+pub fn app_root_dir() -> PathBuf {
+    app_root(AppDataType::UserConfig, &APP_INFO).unwrap()
+}
+
+fn langs_dir() -> PathBuf {
+    app_root_dir().join(LANGS_DIR_NAME)
+}
+
+fn default_lang_path() -> PathBuf {
+    app_root_dir().join(DEFAULT_LANG_NAME)
+}
+
+/// Directory of a named language, the language does not have to exist
+pub fn lang_dir(name: &str) -> Result<PathBuf> {
+    let is_plain_name = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\', ':'].as_ref());
+
+    if !is_plain_name {
+        return Err(InvalidLanguageName(name.to_string()));
+    }
+
+    Ok(langs_dir().join(name))
+}
+
+pub fn exists(name: &str) -> Result<bool> {
+    Ok(lang_dir(name)?.is_dir())
+}
+
+/// Names of all languages, sorted
+pub fn list() -> Result<Vec<String>> {
+    if !langs_dir().is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    for entry in fs::read_dir(langs_dir())? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    names.sort();
+
+    Ok(names)
+}
+
+/// Creates an empty directory for a new language and returns it
+pub fn create(name: &str) -> Result<PathBuf> {
+    if exists(name)? {
+        return Err(LanguageExists(name.to_string()));
+    }
+
+    let dir = lang_dir(name)?;
+    fs::create_dir_all(&dir)?;
+
+    Ok(dir)
+}
+
+/// Deletes a language with all of its files
+pub fn delete(name: &str) -> Result<()> {
+    if !exists(name)? {
+        return Err(UnknownLanguage(name.to_string()));
+    }
+
+    // Waits for commands writing the language, none may start until it is gone
+    let dir = lang_dir(name)?;
+    let _lock = DirLock::acquire(&dir)?;
+    fs::remove_dir_all(&dir)?;
+
+    if default_lang()?.as_deref() == Some(name) {
+        fs::remove_file(default_lang_path())?;
+    }
+
+    Ok(())
+}
+
+/// Language used when none is chosen
+pub fn default_lang() -> Result<Option<String>> {
+    if !default_lang_path().exists() {
+        return Ok(None);
+    }
+
+    let name = fs::read_to_string(default_lang_path())?.trim().to_string();
+
+    if name.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(name))
+    }
+}
+
+pub fn set_default_lang(name: &str) -> Result<()> {
+    if !exists(name)? {
+        return Err(UnknownLanguage(name.to_string()));
+    }
+
+    Ok(fs::write(default_lang_path(), name)?)
+}
+
+/// Directory with config files of a language. The first one set is used of @config_dir,
+/// the language named @lang, LANGGEN_HOME, the default language, or app root
+pub fn resolve_dir(config_dir: Option<&str>, lang: Option<&str>) -> Result<PathBuf> {
+    if let Some(dir) = config_dir {
+        return Ok(PathBuf::from(dir));
+    }
+
+    if lang.is_none() {
+        if let Some(dir) = env::var_os(HOME_VAR_NAME) {
+            return Ok(PathBuf::from(dir));
+        }
+    }
+
+    let name = match lang {
+        Some(name) => Some(name.to_string()),
+        None => default_lang()?,
+    };
+
+    match name {
+        Some(name) => {
+            if !exists(&name)? {
+                return Err(UnknownLanguage(name));
+            }
+            lang_dir(&name)
+        }
+        None => Ok(app_root_dir()),
+    }
+}
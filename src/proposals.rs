@@ -0,0 +1,96 @@
+use crate::error::LangErr::ConfigParse;
+use crate::filesafety;
+use crate::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File inside a language directory with syllables of words proposed by gen
+/// which did not make it into the database
+const PROPOSALS_NAME: &str = "Proposals.txt";
+
+/// Weight of older proposals is multiplied by this with every recorded run
+const HISTORY_DECAY: f64 = 0.8;
+
+/// Weights smaller than this are forgotten
+const MIN_WEIGHT: f64 = 0.01;
+
+/// Share of occurrences in the database the whole history may weigh at most
+const HISTORY_SHARE: f64 = 0.1;
+
+/// (Syllable -> weight) of proposed syllables, a syllable proposed in the latest run
+/// weighs 1, older proposals less. Empty if nothing was recorded
+pub fn read(dir: &Path) -> Result<HashMap<String, f64>> {
+    let content = match fs::read_to_string(dir.join(PROPOSALS_NAME)) {
+        Ok(content) => content,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let mut split = line.rsplitn(2, ':');
+            let weight = split.next().and_then(|w| w.trim().parse::<f64>().ok());
+
+            match (split.next(), weight) {
+                (Some(syllable), Some(weight)) if weight.is_finite() && weight >= 0.0 => {
+                    Ok((syllable.to_string(), weight))
+                }
+                _ => Err(ConfigParse {
+                    file: PROPOSALS_NAME.to_string(),
+                    line: Some(i + 1),
+                    message: "expected syllable:weight".to_string(),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Adds @syllables proposed in a run to the history of the language in @dir,
+/// decaying what was recorded before
+pub fn record(dir: &Path, syllables: &[String]) -> Result<()> {
+    let mut history = read(dir)?;
+
+    for weight in history.values_mut() {
+        *weight *= HISTORY_DECAY;
+    }
+    for syllable in syllables {
+        *history.entry(syllable.to_string()).or_insert(0.0) += 1.0;
+    }
+
+    let mut lines: Vec<String> = history
+        .iter()
+        .filter(|(_, weight)| **weight >= MIN_WEIGHT)
+        .map(|(syllable, weight)| format!("{}:{}\n", syllable, weight))
+        .collect();
+    lines.sort();
+
+    filesafety::write_atomically(&dir.join(PROPOSALS_NAME), lines.concat().as_bytes())
+}
+
+/// @history scaled down so that together it weighs at most HISTORY_SHARE of @occurrences,
+/// a few runs of gen don't outweigh the words of the database then
+pub fn scaled(history: &HashMap<String, f64>, occurrences: f64) -> HashMap<String, f64> {
+    let total: f64 = history.values().sum();
+    let limit = occurrences * HISTORY_SHARE;
+    let scale = if total > limit { limit / total } else { 1.0 };
+
+    history
+        .iter()
+        .map(|(syllable, weight)| (syllable.clone(), weight * scale))
+        .collect()
+}
+
+/// Forgets every proposal of the language in @dir
+pub fn reset(dir: &Path) -> Result<()> {
+    let path = dir.join(PROPOSALS_NAME);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
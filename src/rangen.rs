@@ -1,7 +1,19 @@
 use crate::calculatedrandom::CalculatedRandom;
 use crate::config::LangConfig;
+use crate::error::LangErr;
+use crate::markovrandom::MarkovRandom;
 use crate::realrandom::RealRandom;
+use crate::syllables;
+use crate::tuning::Tuning;
+use crate::weightedrandom::WeightedRandom;
 use crate::Result;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+
+/// Random number generator shared by all engines. Unlike StdRng its output is
+/// guaranteed to be the same on every platform, so a seed always produces the same words
+pub type EngineRng = ChaCha8Rng;
 
 /// For structs that are able to generate words
 pub trait RandomEngine {
@@ -11,19 +23,150 @@ pub trait RandomEngine {
         max_len: u32,
         count: u32,
         cfg: &dyn LangConfig,
-    ) -> Vec<String>;
-    fn with_config(cfg: &dyn LangConfig) -> Result<Self>
+    ) -> Result<Vec<String>>;
+    /// Forgets @word made by the last create_words call, it was thrown away. Engines
+    /// which count what they made stop counting it
+    fn discard(&mut self, _word: &str) {}
+    fn with_config(cfg: &dyn LangConfig, seed: Option<u64>) -> Result<Self>
     where
         Self: Sized;
 }
 
 /// Purely random generation
-pub fn real_random(cfg: &dyn LangConfig) -> Box<dyn RandomEngine> {
-    Box::new(RealRandom::with_config(cfg).unwrap())
+pub fn real_random(cfg: &dyn LangConfig, seed: Option<u64>) -> Result<Box<dyn RandomEngine>> {
+    Ok(Box::new(RealRandom::with_config(cfg, seed)?))
 }
 
 /// Deterministically chooses which syllables will move real occurrence closer to wanted occurrence
-/// and then chooses from the best 10
-pub fn calculated_random(cfg: &dyn LangConfig) -> Box<dyn RandomEngine> {
-    Box::new(CalculatedRandom::with_config(cfg).unwrap())
+/// and then chooses from the best ones, @tuning tells how many and how. Syllables proposed
+/// before count by their weight in @history
+pub fn calculated_random(
+    cfg: &dyn LangConfig,
+    tuning: Tuning,
+    history: &HashMap<String, f64>,
+    seed: Option<u64>,
+) -> Result<Box<dyn RandomEngine>> {
+    Ok(Box::new(CalculatedRandom::with_tuning(
+        cfg, tuning, history, seed,
+    )?))
+}
+
+/// Pulls syllables as often as they are wanted, respecting their positions
+pub fn weighted_random(cfg: &dyn LangConfig, seed: Option<u64>) -> Result<Box<dyn RandomEngine>> {
+    Ok(Box::new(WeightedRandom::with_config(cfg, seed)?))
+}
+
+/// Chains syllables the way they follow each other in the word database,
+/// looking at up to @order previous syllables
+pub fn markov_random(
+    cfg: &dyn LangConfig,
+    order: usize,
+    seed: Option<u64>,
+) -> Result<Box<dyn RandomEngine>> {
+    Ok(Box::new(MarkovRandom::with_order(cfg, order, seed)?))
+}
+
+/// Creates a generator from @seed, or from OS randomness if there is none
+pub fn engine_rng(seed: Option<u64>) -> EngineRng {
+    match seed {
+        Some(seed) => EngineRng::seed_from_u64(seed),
+        None => EngineRng::from_entropy(),
+    }
+}
+
+/// How many times is a word started over when it runs into a syllable that
+/// can't be followed by anything
+const WORD_TRIES: u32 = 10;
+
+/// Creates a word with @create, starting over if it hits a dead end
+pub fn retry_word<F>(mut create: F) -> Result<String>
+where
+    F: FnMut() -> Result<String>,
+{
+    let mut tries = 1;
+    loop {
+        match create() {
+            Err(LangErr::NoValidSyllable { .. }) if tries < WORD_TRIES => tries += 1,
+            result => return result,
+        }
+    }
+}
+
+/// Error for when no syllable fits @pos of a word with @len syllables, @rejected are
+/// the syllables which were tried there and broke position or adjacency rules
+pub fn no_valid_syllable(pos: usize, len: usize, rejected: Vec<String>) -> LangErr {
+    LangErr::NoValidSyllable { pos, len, rejected }
+}
+
+/// Syllables of @cfg which can't follow @previous in a word with @len syllables
+pub fn invalid_syllables(previous: &[String], len: usize, cfg: &dyn LangConfig) -> Vec<String> {
+    cfg.syllables()
+        .iter()
+        .filter(|s| !syllables::is_syllable_valid_after(s, previous, len, cfg))
+        .cloned()
+        .collect()
+}
+
+/// Random index into a collection of @len items. Sampled as u32 so that
+/// 32 and 64 bit machines pull the same index from the same seed
+pub fn random_index(rng: &mut EngineRng, len: usize) -> usize {
+    rng.gen_range(0, len as u32) as usize
+}
+
+/// How many candidates in a row may be rejected before distinct word generation gives up
+const CANDIDATE_TRIES: u32 = 1000;
+
+/// Creates @count words different from each other which are all accepted by @accept.
+/// Fails when CANDIDATE_TRIES candidates in a row are rejected, or right away when there
+/// can't be enough different words with the given length
+pub fn create_distinct_words<F>(
+    engine: &mut dyn RandomEngine,
+    min_len: u32,
+    max_len: u32,
+    count: u32,
+    cfg: &dyn LangConfig,
+    accept: F,
+) -> Result<Vec<String>>
+where
+    F: Fn(&str) -> bool,
+{
+    let exhausted = |found: usize| LangErr::WordSpaceExhausted {
+        wanted: count as usize,
+        found,
+        min_len,
+        max_len,
+    };
+
+    let possible = syllables::count_words(min_len as usize, max_len as usize, cfg);
+    let taken = cfg
+        .database()?
+        .iter()
+        .filter(|e| match syllables::split_into_syllables(&e.word, cfg) {
+            Ok(split) => split.len() >= min_len as usize && split.len() <= max_len as usize,
+            Err(_) => false,
+        })
+        .count() as u128;
+    if possible.saturating_sub(taken) < u128::from(count) {
+        return Err(exhausted(0));
+    }
+
+    let mut result: Vec<String> = Vec::with_capacity(count as usize);
+    let mut rejected_in_row = 0;
+
+    while result.len() < count as usize {
+        let word = engine.create_words(min_len, max_len, 1, cfg)?.remove(0);
+
+        if !result.contains(&word) && accept(&word) {
+            result.push(word);
+            rejected_in_row = 0;
+        } else {
+            engine.discard(&word); // Rejected words must not steer the next ones
+            rejected_in_row += 1;
+            if rejected_in_row >= CANDIDATE_TRIES {
+                return Err(exhausted(result.len()));
+            }
+        }
+    }
+
+    Ok(result)
 }
@@ -1,37 +1,65 @@
 use crate::config::LangConfig;
-use crate::rangen::RandomEngine;
-use crate::{syllables, Result};
-use rand::prelude::ThreadRng;
+use crate::rangen::{EngineRng, RandomEngine};
+use crate::{rangen, syllables, Result};
 use rand::Rng;
 
 /// Returns words and syllables randomly.
-/// Will panic if no words can be generated
+/// Returns error if no syllable fits some position of a word
 pub struct RealRandom {
-    rng: ThreadRng,
+    rng: EngineRng,
     max_tries: u32,
 }
 
 impl RealRandom {
-    fn pull_syllable(&mut self, pos: usize, len: usize, cfg: &dyn LangConfig) -> String {
+    fn pull_syllable(
+        &mut self,
+        previous: &[String],
+        len: usize,
+        cfg: &dyn LangConfig,
+    ) -> Result<String> {
+        if cfg.syllables().is_empty() {
+            return Err(rangen::no_valid_syllable(previous.len(), len, Vec::new()));
+        }
+
         let mut tries = 0;
         while tries < self.max_tries {
             let syllable = cfg
                 .syllables() // Pull random syllable
-                .get(self.rng.gen_range(0, cfg.syllables().len()))
+                .get(rangen::random_index(&mut self.rng, cfg.syllables().len()))
                 .unwrap(); // Can never be out of bounds
 
-            if syllables::is_syllable_pos_valid(&syllable, pos, len, cfg) {
+            if syllables::is_syllable_valid_after(syllable, previous, len, cfg) {
                 // Check if it is valid
-                return syllable.to_string(); // Return if OK
+                return Ok(syllable.to_string()); // Return if OK
             } // Otherwise try again
 
             tries += 1; // Keep at bottom
         }
 
-        panic!(
-            "Could not get a suitable syllable in {} tries",
-            self.max_tries
-        )
+        // Valid syllables are rare here, pick from all of them instead of guessing
+        let valid: Vec<&String> = cfg
+            .syllables()
+            .iter()
+            .filter(|s| syllables::is_syllable_valid_after(s, previous, len, cfg))
+            .collect();
+
+        if valid.is_empty() {
+            let rejected = rangen::invalid_syllables(previous, len, cfg);
+            return Err(rangen::no_valid_syllable(previous.len(), len, rejected));
+        }
+
+        Ok(valid[rangen::random_index(&mut self.rng, valid.len())].to_string())
+    }
+
+    fn create_word(&mut self, len: usize, cfg: &dyn LangConfig) -> Result<String> {
+        let mut syllables = Vec::with_capacity(len);
+
+        for _pos in 0..len {
+            let syllable = self.pull_syllable(&syllables, len, cfg)?;
+            syllables.push(syllable);
+        }
+
+        Ok(syllables.concat())
     }
 }
 
@@ -42,29 +70,25 @@ impl RandomEngine for RealRandom {
         max_len: u32,
         count: u32,
         cfg: &dyn LangConfig,
-    ) -> Vec<String> {
+    ) -> Result<Vec<String>> {
         let mut result = Vec::with_capacity(count as usize);
 
         for _nth_word in 0..count {
-            let chosen_length = self.rng.gen_range(min_len, max_len + 1);
-            let mut word = String::new();
-
-            for pos in 0..chosen_length {
-                word.push_str(&self.pull_syllable(pos as usize, chosen_length as usize, cfg));
-            }
+            let chosen_length = self.rng.gen_range(min_len, max_len + 1) as usize;
+            let word = rangen::retry_word(|| self.create_word(chosen_length, cfg))?;
 
             result.push(word);
         }
 
-        result
+        Ok(result)
     }
 
-    fn with_config(_cfg: &dyn LangConfig) -> Result<RealRandom>
+    fn with_config(_cfg: &dyn LangConfig, seed: Option<u64>) -> Result<RealRandom>
     where
         Self: Sized,
     {
         Ok(RealRandom {
-            rng: rand::thread_rng(),
+            rng: rangen::engine_rng(seed),
             max_tries: 100,
         })
     }
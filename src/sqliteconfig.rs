@@ -0,0 +1,668 @@
+use crate::config::LangConfig;
+use crate::error::LangErr::{ConfigParse, MissingFile};
+use crate::languagepack::{LanguagePack, SyllableInfo};
+use crate::lexicon::LexiconEntry;
+use crate::lint::{self, LintIssue};
+use crate::phonology::Phonology;
+use crate::phonotactics::AdjacencyRule;
+use crate::syllables::SyllablePosition;
+use crate::tuning::Tuning;
+use crate::wanted::{Wanted, WordPosition};
+use crate::Result;
+use rusqlite::{params, Connection, OpenFlags, Row, Transaction};
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DATABASE_FILE_NAME: &str = "language.db";
+
+/// Separates tags inside the tags column, same as in the text database
+const TAG_SEPARATOR: &str = ",";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS syllables (
+        ord INTEGER PRIMARY KEY,
+        syllable TEXT NOT NULL UNIQUE,
+        position TEXT,
+        romanization TEXT,
+        wanted REAL,
+        wanted_start REAL,
+        wanted_middle REAL,
+        wanted_end REAL
+    );
+    CREATE TABLE IF NOT EXISTS phoneme_classes (
+        class TEXT PRIMARY KEY,
+        phonemes TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS templates (
+        ord INTEGER PRIMARY KEY,
+        template TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS adjacency_rules (
+        ord INTEGER PRIMARY KEY,
+        rule TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tuning (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS words (
+        id INTEGER PRIMARY KEY,
+        word TEXT NOT NULL,
+        gloss TEXT,
+        part_of_speech TEXT,
+        tags TEXT,
+        created TEXT,
+        notes TEXT
+    );
+    CREATE INDEX IF NOT EXISTS words_word ON words (word);
+";
+
+/// Columns added to the syllables table after it was first created
+const POSITIONAL_WANTED_COLUMNS: [&str; 3] = ["wanted_start", "wanted_middle", "wanted_end"];
+
+/// Columns of the words table an entry is made of, in the order row_entry reads them
+const ENTRY_COLUMNS: &str = "word, gloss, part_of_speech, tags, created, notes";
+
+/// Change of the word database not written yet
+enum WordChange {
+    Add(LexiconEntry),
+    /// First entry equal to this one is deleted
+    Delete(LexiconEntry),
+}
+
+/// Language config stored in an embedded SQLite database. Only changed parts are
+/// written on flush, words are added and deleted one by one in a single transaction.
+/// Words are only read when they are asked for, adding and deleting does not need them
+pub struct SqliteConfig {
+    // DATA
+    syllables: Vec<String>,
+    syllable_pos: HashMap<String, SyllablePosition>,
+    utf8_to_ascii: HashMap<String, String>,
+    wanted: HashMap<String, Wanted>,
+    phonology: Phonology,
+    adjacency_rules: Vec<AdjacencyRule>,
+    tuning: Tuning,
+    // Read on first use, see database
+    database: OnceCell<Vec<LexiconEntry>>,
+    // CHANGES
+    syllables_changed: bool,
+    phonology_changed: bool,
+    adjacency_changed: bool,
+    tuning_changed: bool,
+    // All words are written again instead of the changes
+    database_replaced: bool,
+    word_changes: Vec<WordChange>,
+    // FILE PATHS
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl SqliteConfig {
+    pub fn in_dir(dir: &Path) -> SqliteConfig {
+        SqliteConfig {
+            syllables: Vec::new(),
+            syllable_pos: HashMap::new(),
+            utf8_to_ascii: HashMap::new(),
+            wanted: HashMap::new(),
+            phonology: Phonology::default(),
+            adjacency_rules: Vec::new(),
+            tuning: Tuning::default(),
+            database: OnceCell::new(),
+            syllables_changed: false,
+            phonology_changed: false,
+            adjacency_changed: false,
+            tuning_changed: false,
+            database_replaced: false,
+            word_changes: Vec::new(),
+            dir: dir.to_path_buf(),
+            path: dir.join(DATABASE_FILE_NAME),
+        }
+    }
+
+    /// Opens existing database, it is not created if it does not exist
+    fn open_existing(&self) -> Result<Connection> {
+        if !self.path.exists() {
+            return Err(MissingFile(self.path.display().to_string()));
+        }
+
+        Ok(Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?)
+    }
+
+    fn read_pack(&self) -> Result<LanguagePack> {
+        let conn = self.open_existing()?;
+        let mut pack = LanguagePack::default();
+
+        // Databases written before positional wanted values were stored don't have them
+        let positional = if has_column(&conn, "syllables", POSITIONAL_WANTED_COLUMNS[0])? {
+            POSITIONAL_WANTED_COLUMNS.join(", ")
+        } else {
+            "NULL, NULL, NULL".to_string()
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT syllable, position, romanization, wanted, {} FROM syllables ORDER BY ord",
+            positional
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<f64>>(3)?,
+                [
+                    row.get::<_, Option<f64>>(4)?,
+                    row.get::<_, Option<f64>>(5)?,
+                    row.get::<_, Option<f64>>(6)?,
+                ],
+            ))
+        })?;
+        for row in rows {
+            let (syllable, position, romanization, wanted, positional) = row?;
+            let position = match position {
+                Some(keyword) => {
+                    Some(SyllablePosition::from_keyword(&keyword).ok_or_else(|| {
+                        parse_err(format!(
+                            "unknown position keyword '{}' of {}",
+                            keyword, syllable
+                        ))
+                    })?)
+                }
+                None => None,
+            };
+
+            pack.syllables.push(SyllableInfo {
+                syllable,
+                position,
+                romanization,
+                wanted,
+                wanted_start: positional[0],
+                wanted_middle: positional[1],
+                wanted_end: positional[2],
+            });
+        }
+
+        let mut stmt = conn.prepare("SELECT class, phonemes FROM phoneme_classes")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (class, phonemes) = row?;
+            let mut chars = class.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    let phonemes = phonemes.split(',').map(|p| p.trim().to_string()).collect();
+                    pack.phonology.classes.insert(c, phonemes);
+                }
+                _ => {
+                    return Err(parse_err(format!(
+                        "phoneme class '{}' is not a single char",
+                        class
+                    )))
+                }
+            }
+        }
+
+        let mut stmt = conn.prepare("SELECT template FROM templates ORDER BY ord")?;
+        for template in stmt.query_map([], |row| row.get::<_, String>(0))? {
+            pack.phonology.templates.push(template?);
+        }
+
+        let mut stmt = conn.prepare("SELECT rule FROM adjacency_rules ORDER BY ord")?;
+        for rule in stmt.query_map([], |row| row.get::<_, String>(0))? {
+            pack.adjacency
+                .push(AdjacencyRule::from_line(&rule?).map_err(parse_err)?);
+        }
+
+        // Databases written before tuning was stored don't have the table
+        let has_tuning: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'tuning')",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_tuning {
+            let mut stmt = conn.prepare("SELECT key, value FROM tuning")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                let (key, value) = row?;
+                pack.tuning.set(&key, &value).map_err(parse_err)?;
+            }
+        }
+
+        Ok(pack)
+    }
+
+    /// Every word in the database, none if there is no database yet
+    fn read_words(&self) -> Result<Vec<LexiconEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.open_existing()?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM words ORDER BY id", ENTRY_COLUMNS))?;
+        let words = stmt
+            .query_map([], row_entry)?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(words)
+    }
+
+    /// Entries of @word in the order database lists them, found through the index
+    /// of the words table and changes not written yet instead of reading every word
+    fn entries_of(&self, word: &str) -> Result<Vec<LexiconEntry>> {
+        let mut entries: Vec<LexiconEntry> = if self.path.exists() {
+            let conn = self.open_existing()?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM words WHERE word = ?1 ORDER BY id",
+                ENTRY_COLUMNS
+            ))?;
+            let entries = stmt
+                .query_map(params![word], row_entry)?
+                .collect::<rusqlite::Result<_>>()?;
+            entries
+        } else {
+            Vec::new()
+        };
+
+        for change in &self.word_changes {
+            match change {
+                WordChange::Add(entry) if entry.word == word => entries.push(entry.clone()),
+                WordChange::Delete(entry) if entry.word == word => {
+                    if let Some(i) = entries.iter().position(|e| e == entry) {
+                        entries.remove(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Deletes the first entry of @word accepted by @accept and returns it
+    fn delete_first<F>(&mut self, word: &str, accept: F) -> Result<Option<LexiconEntry>>
+    where
+        F: Fn(&LexiconEntry) -> bool,
+    {
+        let entry = match self.database.get_mut() {
+            Some(database) => database
+                .iter()
+                .position(|e| e.word == word && accept(e))
+                .map(|i| database.remove(i)),
+            None => self.entries_of(word)?.into_iter().find(|e| accept(e)),
+        };
+
+        if let Some(entry) = &entry {
+            self.word_changes.push(WordChange::Delete(entry.clone()));
+        }
+
+        Ok(entry)
+    }
+
+    fn write_syllables(&self, tx: &Transaction) -> Result<()> {
+        tx.execute("DELETE FROM syllables", [])?;
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO syllables (ord, syllable, position, romanization, wanted,
+                wanted_start, wanted_middle, wanted_end)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for (i, s) in self.syllables.iter().enumerate() {
+            let wanted = self.wanted.get(s);
+            let at = |position| wanted.and_then(|w| w.only_at(position));
+            stmt.execute(params![
+                i as i64,
+                s,
+                self.syllable_pos.get(s).map(|p| p.to_string()),
+                self.utf8_to_ascii.get(s),
+                wanted.map(|w| w.overall),
+                at(WordPosition::Start),
+                at(WordPosition::Middle),
+                at(WordPosition::End),
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    fn write_phonology(&self, tx: &Transaction) -> Result<()> {
+        tx.execute("DELETE FROM phoneme_classes", [])?;
+        tx.execute("DELETE FROM templates", [])?;
+
+        for (class, phonemes) in &self.phonology.classes {
+            tx.execute(
+                "INSERT INTO phoneme_classes (class, phonemes) VALUES (?1, ?2)",
+                params![class.to_string(), phonemes.join(",")],
+            )?;
+        }
+        for (i, template) in self.phonology.templates.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO templates (ord, template) VALUES (?1, ?2)",
+                params![i as i64, template],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_adjacency_rules(&self, tx: &Transaction) -> Result<()> {
+        tx.execute("DELETE FROM adjacency_rules", [])?;
+
+        for (i, rule) in self.adjacency_rules.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO adjacency_rules (ord, rule) VALUES (?1, ?2)",
+                params![i as i64, rule.to_string()],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_tuning(&self, tx: &Transaction) -> Result<()> {
+        tx.execute("DELETE FROM tuning", [])?;
+
+        for (key, value) in self.tuning.pairs() {
+            tx.execute(
+                "INSERT INTO tuning (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_word_changes(&self, tx: &Transaction) -> Result<()> {
+        if self.database_replaced {
+            tx.execute("DELETE FROM words", [])?;
+            for entry in self.database()? {
+                insert_word(tx, entry)?;
+            }
+            return Ok(());
+        }
+
+        for change in &self.word_changes {
+            match change {
+                WordChange::Add(entry) => insert_word(tx, entry)?,
+                WordChange::Delete(entry) => {
+                    // Only the first equal entry is deleted, same as in memory
+                    tx.execute(
+                        "DELETE FROM words WHERE id = (SELECT id FROM words
+                         WHERE word = ?1 AND gloss IS ?2 AND part_of_speech IS ?3
+                             AND tags IS ?4 AND created IS ?5 AND notes IS ?6
+                         ORDER BY id LIMIT 1)",
+                        params![
+                            entry.word,
+                            entry.gloss,
+                            entry.part_of_speech,
+                            tags_column(entry),
+                            entry.created,
+                            entry.notes
+                        ],
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear_changes(&mut self) {
+        self.syllables_changed = false;
+        self.phonology_changed = false;
+        self.adjacency_changed = false;
+        self.tuning_changed = false;
+        self.database_replaced = false;
+        self.word_changes.clear();
+    }
+}
+
+/// Tags of @entry as they are stored in the tags column
+fn tags_column(entry: &LexiconEntry) -> Option<String> {
+    if entry.tags.is_empty() {
+        None
+    } else {
+        Some(entry.tags.join(TAG_SEPARATOR))
+    }
+}
+
+/// Entry of a row with ENTRY_COLUMNS
+fn row_entry(row: &Row) -> rusqlite::Result<LexiconEntry> {
+    let tags: Option<String> = row.get(3)?;
+
+    Ok(LexiconEntry {
+        word: row.get(0)?,
+        gloss: row.get(1)?,
+        part_of_speech: row.get(2)?,
+        tags: tags
+            .unwrap_or_default()
+            .split(TAG_SEPARATOR)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect(),
+        created: row.get(4)?,
+        notes: row.get(5)?,
+    })
+}
+
+fn insert_word(tx: &Transaction, entry: &LexiconEntry) -> Result<()> {
+    tx.execute(
+        "INSERT INTO words (word, gloss, part_of_speech, tags, created, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entry.word,
+            entry.gloss,
+            entry.part_of_speech,
+            tags_column(entry),
+            entry.created,
+            entry.notes
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Checks whether @table has @column
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let mut names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    Ok(names.any(|name| name.map(|n| n == column).unwrap_or(false)))
+}
+
+fn parse_err(message: String) -> crate::error::LangErr {
+    ConfigParse {
+        file: DATABASE_FILE_NAME.to_string(),
+        line: None,
+        message,
+    }
+}
+
+impl LangConfig for SqliteConfig {
+    fn syllables(&self) -> &Vec<String> {
+        &self.syllables
+    }
+
+    fn set_syllables(&mut self, syllables: Vec<String>) {
+        self.syllables = syllables;
+        self.syllables_changed = true;
+    }
+
+    fn syllable_pos(&self) -> &HashMap<String, SyllablePosition> {
+        &self.syllable_pos
+    }
+
+    fn set_syllable_pos(&mut self, syllable_pos: HashMap<String, SyllablePosition>) {
+        self.syllable_pos = syllable_pos;
+        self.syllables_changed = true;
+    }
+
+    fn romanization(&self) -> &HashMap<String, String> {
+        &self.utf8_to_ascii
+    }
+
+    fn set_romanization(&mut self, utf_to_ascii: HashMap<String, String>) {
+        self.utf8_to_ascii = utf_to_ascii;
+        self.syllables_changed = true;
+    }
+
+    fn wanted(&self) -> &HashMap<String, Wanted> {
+        &self.wanted
+    }
+
+    fn set_wanted(&mut self, wanted: HashMap<String, Wanted>) {
+        self.wanted = wanted;
+        self.syllables_changed = true;
+    }
+
+    fn phonology(&self) -> &Phonology {
+        &self.phonology
+    }
+
+    fn set_phonology(&mut self, phonology: Phonology) {
+        self.phonology = phonology;
+        self.phonology_changed = true;
+    }
+
+    fn adjacency_rules(&self) -> &Vec<AdjacencyRule> {
+        &self.adjacency_rules
+    }
+
+    fn set_adjacency_rules(&mut self, rules: Vec<AdjacencyRule>) {
+        self.adjacency_rules = rules;
+        self.adjacency_changed = true;
+    }
+
+    fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+        self.tuning_changed = true;
+    }
+
+    fn database(&self) -> Result<&Vec<LexiconEntry>> {
+        if let Some(words) = self.database.get() {
+            return Ok(words);
+        }
+
+        // Load does not read the words, changes made since then are applied to them
+        let mut words = self.read_words()?;
+        for change in &self.word_changes {
+            match change {
+                WordChange::Add(entry) => words.push(entry.clone()),
+                WordChange::Delete(entry) => {
+                    if let Some(i) = words.iter().position(|e| e == entry) {
+                        words.remove(i);
+                    }
+                }
+            }
+        }
+
+        Ok(self.database.get_or_init(|| words))
+    }
+
+    fn set_database(&mut self, db: Vec<LexiconEntry>) {
+        self.database = OnceCell::from(db);
+        self.database_replaced = true;
+        self.word_changes.clear();
+    }
+
+    fn append_database(&mut self, entries: &[LexiconEntry]) {
+        if let Some(database) = self.database.get_mut() {
+            database.extend_from_slice(entries);
+        }
+        self.word_changes
+            .extend(entries.iter().cloned().map(WordChange::Add));
+    }
+
+    fn delete_from_database(&mut self, word: &str) -> Result<Option<LexiconEntry>> {
+        self.delete_first(word, |_| true)
+    }
+
+    fn delete_entry_from_database(&mut self, entry: &LexiconEntry) -> Result<bool> {
+        Ok(self.delete_first(&entry.word, |e| e == entry)?.is_some())
+    }
+
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn load(&mut self) -> Result<()> {
+        self.read_pack()?.apply_to(self);
+        self.database = OnceCell::new(); // Words are read when they are asked for
+        self.clear_changes(); // Nothing changed by loading
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+
+        Ok(())
+    }
+
+    fn lint(&self) -> Result<Vec<LintIssue>> {
+        let pack = self.read_pack().and_then(|mut pack| {
+            pack.words = self.read_words()?;
+            Ok(pack)
+        });
+
+        match pack {
+            Ok(pack) => Ok(lint::lint_pack(DATABASE_FILE_NAME, &pack)),
+            Err(ConfigParse {
+                file,
+                line,
+                message,
+            }) => Ok(vec![LintIssue::new(&file, line, message)]),
+            Err(MissingFile(_)) => Ok(vec![LintIssue::new(
+                DATABASE_FILE_NAME,
+                None,
+                "file does not exist".to_string(),
+            )]),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let mut conn = Connection::open(&self.path)?;
+        conn.execute_batch(SCHEMA)?;
+        for column in POSITIONAL_WANTED_COLUMNS.iter() {
+            if !has_column(&conn, "syllables", column)? {
+                conn.execute(
+                    &format!("ALTER TABLE syllables ADD COLUMN {} REAL", column),
+                    [],
+                )?;
+            }
+        }
+
+        let tx = conn.transaction()?;
+        if self.syllables_changed {
+            self.write_syllables(&tx)?;
+        }
+        if self.phonology_changed {
+            self.write_phonology(&tx)?;
+        }
+        if self.adjacency_changed {
+            self.write_adjacency_rules(&tx)?;
+        }
+        if self.tuning_changed {
+            self.write_tuning(&tx)?;
+        }
+        self.write_word_changes(&tx)?;
+        tx.commit()?;
+
+        self.clear_changes();
+
+        Ok(())
+    }
+}
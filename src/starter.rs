@@ -0,0 +1,199 @@
+use crate::config::LangConfig;
+use crate::phonology::Phonology;
+use crate::phonotactics::AdjacencyRule;
+use crate::syllables::SyllablePosition;
+use crate::tuning::Tuning;
+use crate::wanted::Wanted;
+use crate::Result;
+use std::collections::HashMap;
+
+/// Names of templates a new language can start from
+pub const TEMPLATE_NAMES: [&str; 4] = ["cv", "cvc", "japanese-like", "empty"];
+
+/// Config of a new language
+pub struct Starter {
+    pub phonology: Phonology,
+    pub adjacency_rules: Vec<AdjacencyRule>,
+    /// (Syllable -> position), syllables without one may be anywhere
+    pub positions: HashMap<String, SyllablePosition>,
+    /// (Syllable -> UTF8 form), syllables without one are written the same way
+    pub utf8_forms: HashMap<String, String>,
+}
+
+impl Starter {
+    /// Starter of template @name, None if there is no such template
+    pub fn named(name: &str) -> Option<Starter> {
+        match name {
+            "cv" => Some(cv()),
+            "cvc" => Some(cvc()),
+            "japanese-like" => Some(japanese_like()),
+            "empty" => Some(empty()),
+            _ => None,
+        }
+    }
+
+    /// Replaces config in @cfg with this starter. Syllables are generated from phonology,
+    /// all of them are wanted equally, tuning is the default one and the database is emptied
+    pub fn apply(self, cfg: &mut dyn LangConfig) -> Result<()> {
+        let syllables = if self.phonology.templates.is_empty() {
+            Vec::new()
+        } else {
+            self.phonology.generate_syllables()?
+        };
+
+        let mut positions = HashMap::new();
+        let mut utf8_forms = HashMap::new();
+        let mut wanted = HashMap::new();
+        let share = 1.0 / syllables.len() as f64;
+
+        for s in &syllables {
+            let pos = self
+                .positions
+                .get(s)
+                .cloned()
+                .unwrap_or(SyllablePosition::Any);
+            let utf8 = self.utf8_forms.get(s).cloned().unwrap_or_else(|| s.clone());
+
+            positions.insert(s.clone(), pos);
+            utf8_forms.insert(s.clone(), utf8);
+            wanted.insert(s.clone(), Wanted::overall(share));
+        }
+
+        cfg.set_phonology(self.phonology);
+        cfg.set_adjacency_rules(self.adjacency_rules);
+        cfg.set_syllables(syllables);
+        cfg.set_syllable_pos(positions);
+        cfg.set_romanization(utf8_forms);
+        cfg.set_wanted(wanted);
+        cfg.set_tuning(Tuning::default());
+        cfg.set_database(Vec::new());
+
+        Ok(())
+    }
+}
+
+fn classes(classes: &[(char, &str)]) -> Phonology {
+    Phonology {
+        classes: classes
+            .iter()
+            .map(|(class, phonemes)| (*class, phonemes.split(',').map(str::to_string).collect()))
+            .collect(),
+        templates: Vec::new(),
+    }
+}
+
+/// Open syllables of common consonants and the five basic vowels
+fn cv() -> Starter {
+    let mut phonology = classes(&[('C', "p,t,k,m,n,s,l"), ('V', "a,e,i,o,u")]);
+    phonology.templates.push("CV".to_string());
+
+    Starter {
+        phonology,
+        adjacency_rules: vec![AdjacencyRule::ForbidRepeat],
+        positions: HashMap::new(),
+        utf8_forms: HashMap::new(),
+    }
+}
+
+/// Open syllables and syllables closed by a nasal or liquid
+fn cvc() -> Starter {
+    let mut phonology = classes(&[
+        ('C', "p,t,k,b,d,g,m,n,s,l,r"),
+        ('V', "a,e,i,o,u"),
+        ('N', "m,n,l,r"),
+    ]);
+    phonology.templates.push("CV(N)".to_string());
+
+    Starter {
+        phonology,
+        adjacency_rules: vec![AdjacencyRule::ForbidRepeat],
+        positions: HashMap::new(),
+        utf8_forms: HashMap::new(),
+    }
+}
+
+/// Romanized kana syllables written as hiragana
+fn japanese_like() -> Starter {
+    const KANA: [(&str, &str); 46] = [
+        ("a", "あ"),
+        ("i", "い"),
+        ("u", "う"),
+        ("e", "え"),
+        ("o", "お"),
+        ("ka", "か"),
+        ("ki", "き"),
+        ("ku", "く"),
+        ("ke", "け"),
+        ("ko", "こ"),
+        ("sa", "さ"),
+        ("shi", "し"),
+        ("su", "す"),
+        ("se", "せ"),
+        ("so", "そ"),
+        ("ta", "た"),
+        ("chi", "ち"),
+        ("tsu", "つ"),
+        ("te", "て"),
+        ("to", "と"),
+        ("na", "な"),
+        ("ni", "に"),
+        ("nu", "ぬ"),
+        ("ne", "ね"),
+        ("no", "の"),
+        ("ha", "は"),
+        ("hi", "ひ"),
+        ("fu", "ふ"),
+        ("he", "へ"),
+        ("ho", "ほ"),
+        ("ma", "ま"),
+        ("mi", "み"),
+        ("mu", "む"),
+        ("me", "め"),
+        ("mo", "も"),
+        ("ya", "や"),
+        ("yu", "ゆ"),
+        ("yo", "よ"),
+        ("ra", "ら"),
+        ("ri", "り"),
+        ("ru", "る"),
+        ("re", "れ"),
+        ("ro", "ろ"),
+        ("wa", "わ"),
+        ("wo", "を"),
+        ("n", "ん"),
+    ];
+
+    let mut phonology = classes(&[('V', "a,i,u,e,o")]);
+    // Every kana is its own template, gaps such as "yi" or "si" don't fit a CV template
+    phonology.templates = KANA
+        .iter()
+        .map(|(syllable, _)| syllable.to_string())
+        .collect();
+
+    let mut positions = HashMap::new();
+    positions.insert("n".to_string(), SyllablePosition::NotStart);
+
+    Starter {
+        phonology,
+        // "n" followed by a vowel would be read as a "na" like syllable
+        adjacency_rules: vec![
+            AdjacencyRule::Forbid("n".to_string(), "n".to_string()),
+            AdjacencyRule::Forbid("n".to_string(), "V*".to_string()),
+        ],
+        positions,
+        utf8_forms: KANA
+            .iter()
+            .map(|(syllable, kana)| (syllable.to_string(), kana.to_string()))
+            .collect(),
+    }
+}
+
+/// No syllables at all, everything is written by hand
+fn empty() -> Starter {
+    Starter {
+        phonology: Phonology::default(),
+        adjacency_rules: Vec::new(),
+        positions: HashMap::new(),
+        utf8_forms: HashMap::new(),
+    }
+}
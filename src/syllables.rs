@@ -1,13 +1,16 @@
 use crate::config::LangConfig;
-use crate::error::LangErr::InvalidSyllable;
+use crate::error::LangErr::{InvalidSyllable, InvalidWord};
+use crate::phonotactics;
+use crate::wanted::WordPosition;
 use crate::Result;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::borrow::Borrow;
-use std::cmp;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 /// Represents where in a word a syllable is
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum SyllablePosition {
     Start,
     NotStart,
@@ -18,104 +21,463 @@ pub enum SyllablePosition {
     Any,
 }
 
-impl From<String> for SyllablePosition {
-    fn from(string: String) -> Self {
-        match string.to_lowercase().borrow() {
-            "start" => SyllablePosition::Start,
-            "notstart" => SyllablePosition::NotStart,
-            "end" => SyllablePosition::End,
-            "notend" => SyllablePosition::NotEnd,
-            "middle" => SyllablePosition::Middle,
-            "notmiddle" => SyllablePosition::NotMiddle,
-            "any" => SyllablePosition::Any,
-            _ => panic!("Unexpected syllable position"),
+impl SyllablePosition {
+    /// Position named by a keyword of the syllable positions file, case is ignored
+    pub fn from_keyword(keyword: &str) -> Option<SyllablePosition> {
+        match keyword.to_lowercase().borrow() {
+            "start" => Some(SyllablePosition::Start),
+            "notstart" => Some(SyllablePosition::NotStart),
+            "end" => Some(SyllablePosition::End),
+            "notend" => Some(SyllablePosition::NotEnd),
+            "middle" => Some(SyllablePosition::Middle),
+            "notmiddle" => Some(SyllablePosition::NotMiddle),
+            "any" => Some(SyllablePosition::Any),
+            _ => None,
         }
     }
 }
 
-/// Splits a single word into syllables. Returns
+impl fmt::Display for SyllablePosition {
+    /// Same keyword as is used in the syllable positions file
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self {
+            SyllablePosition::Start => "start",
+            SyllablePosition::NotStart => "notstart",
+            SyllablePosition::End => "end",
+            SyllablePosition::NotEnd => "notend",
+            SyllablePosition::Middle => "middle",
+            SyllablePosition::NotMiddle => "notmiddle",
+            SyllablePosition::Any => "any",
+        };
+        write!(f, "{}", keyword)
+    }
+}
+
+// Positions are stored by their keyword
+impl Serialize for SyllablePosition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SyllablePosition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let keyword = String::deserialize(deserializer)?;
+        SyllablePosition::from_keyword(&keyword)
+            .ok_or_else(|| de::Error::custom(format!("unknown position keyword '{}'", keyword)))
+    }
+}
+
+/// Splits a single word into syllables. If the word can be split in more than one way,
+/// the split with the fewest (longest) syllables is used. Returns
 /// error if word can't be split
 pub fn split_into_syllables(word: &str, cfg: &dyn LangConfig) -> Result<Vec<String>> {
-    assert!(word.trim() == word, "Whitespace in word is not allowed");
+    let steps = split_steps(word, cfg)?;
+    let (fewest, _) = split_lengths(word, &steps);
+
+    let mut result = Vec::new();
+    let mut start = 0;
+    while start < word.len() {
+        // First step in preferred order which keeps the count lowest, there is always one
+        let syllable = steps[start]
+            .iter()
+            .find(|s| fewest[start + s.len()] + 1 == fewest[start])
+            .unwrap();
+
+        result.push(syllable.to_string());
+        start += syllable.len();
+    }
+
+    Ok(result)
+}
+
+/// Finds up to @max ways a single word can be split into syllables, preferred split first
+/// (see split_into_syllables). Returns error if word can't be split
+pub fn all_splits(word: &str, max: usize, cfg: &dyn LangConfig) -> Result<Vec<Vec<String>>> {
+    let steps = split_steps(word, cfg)?;
+    let (fewest, most) = split_lengths(word, &steps);
+
+    let mut result = Vec::new();
+    // Walked length by length, so splits with fewer syllables come first
+    for len in fewest[0]..=most[0] {
+        let walk = SplitWalk {
+            steps: &steps,
+            fewest: &fewest,
+            most: &most,
+            len,
+            max,
+        };
+        walk.collect(0, &mut Vec::new(), &mut result);
+    }
 
-    let mut syllables = cfg.syllables().clone();
-    syllables.sort_by(|a, b| a.len().cmp(&b.len())); // Longer syllables must come first
+    Ok(result)
+}
 
-    let max_len = syllables
-        .iter() // Unicode characters may be longer than thay appear,
-        .map(String::len) // this is working as intended
-        .max()
-        .unwrap(); // Longest syllable
+/// How many ways a single word can be split into syllables, u128::MAX if there are more.
+/// Returns error if word can't be split
+pub fn count_splits(word: &str, cfg: &dyn LangConfig) -> Result<u128> {
+    let steps = split_steps(word, cfg)?;
+
+    // ways[i] is the number of splits of word[i..]
+    let mut ways = vec![0u128; word.len() + 1];
+    ways[word.len()] = 1;
+    for i in (0..word.len()).rev() {
+        ways[i] = steps[i]
+            .iter()
+            .fold(0u128, |sum, s| sum.saturating_add(ways[i + s.len()]));
+    }
 
-    let min_len = syllables.iter().map(String::len).min().unwrap(); // Shortest syllable
+    Ok(ways[0])
+}
 
-    let mut syl_res = Vec::new(); // Function result
+/// steps[i] are syllables word[i..] starts with after which the rest can still be split,
+/// longer syllables first. Returns error if word can't be split
+fn split_steps<'a>(word: &str, cfg: &'a dyn LangConfig) -> Result<Vec<Vec<&'a String>>> {
+    if word.chars().any(char::is_whitespace) {
+        return Err(InvalidWord(
+            word.to_string(),
+            "whitespace in word is not allowed".to_string(),
+        ));
+    }
 
-    let mut char_count = 0; // Total chars in syl_res
-    let mut char_len = max_len; // How many chars at once are we checking
+    let mut syllables: Vec<&String> = cfg.syllables().iter().filter(|s| !s.is_empty()).collect();
+    // Longer syllables are tried first so the splits come out in preferred order
+    syllables.sort_by(|a, b| b.chars().count().cmp(&a.chars().count()).then(a.cmp(b)));
+
+    // can_finish[i] is true if word[i..] can be split into syllables. Syllables are
+    // whole strings, so every index they lead to lies on a char boundary
+    let mut can_finish = vec![false; word.len() + 1];
+    can_finish[word.len()] = true;
+    let mut steps = vec![Vec::new(); word.len() + 1];
+
+    for i in (0..word.len()).rev().filter(|i| word.is_char_boundary(*i)) {
+        steps[i] = syllables
+            .iter()
+            .filter(|s| word[i..].starts_with(s.as_str()) && can_finish[i + s.len()])
+            .cloned()
+            .collect();
+        can_finish[i] = !steps[i].is_empty();
+    }
 
-    while char_count != word.len() {
-        // Go from last char checked to max syllable length or end of arr
-        let part = &word[char_count..(char_count + cmp::min(char_len, word.len()))];
+    if !can_finish[0] {
+        return Err(InvalidSyllable(unsplittable_part(word, &syllables)));
+    }
 
-        if cfg.syllables().iter().any(|s| s == part) {
-            // Part is found in syllables (iter any used instead of contains because &String != &str)
-            syl_res.push(part.to_string()); // Add it to result
-            char_count += part.len(); // Increment char count
-        } else if char_len - 1 < min_len {
-            // Can't decrease syl_size, it would be bellow min
-            return Err(InvalidSyllable(part.to_string())); // Invalid syllable found
-        } else {
-            char_len -= 1; // Syllable with wanted  length not found, decreasing length
+    Ok(steps)
+}
+
+/// (fewest, most) where fewest[i] and most[i] are the lowest and highest number of syllables
+/// word[i..] can be split into. Both are 0 where no split can start
+fn split_lengths(word: &str, steps: &[Vec<&String>]) -> (Vec<usize>, Vec<usize>) {
+    let mut fewest = vec![0; word.len() + 1];
+    let mut most = vec![0; word.len() + 1];
+
+    for i in (0..word.len()).rev() {
+        // Every step leads to an index the rest can be split from
+        let after: Vec<usize> = steps[i].iter().map(|s| i + s.len()).collect();
+        if let Some(min) = after.iter().map(|a| fewest[*a]).min() {
+            fewest[i] = min + 1;
+        }
+        if let Some(max) = after.iter().map(|a| most[*a]).max() {
+            most[i] = max + 1;
+        }
+    }
+
+    (fewest, most)
+}
+
+/// Walk over splits with exactly @len syllables, in preferred order
+struct SplitWalk<'a> {
+    steps: &'a [Vec<&'a String>],
+    fewest: &'a [usize],
+    most: &'a [usize],
+    len: usize,
+    // Walk stops once result has this many splits
+    max: usize,
+}
+
+impl<'a> SplitWalk<'a> {
+    /// Adds splits of word[start..] following @current to @result
+    fn collect(&self, start: usize, current: &mut Vec<String>, result: &mut Vec<Vec<String>>) {
+        if result.len() >= self.max {
+            return;
+        }
+        if start == self.steps.len() - 1 {
+            if current.len() == self.len {
+                result.push(current.clone());
+            }
+            return;
+        }
+
+        // Only steps after which the rest can be split into the syllables left are taken
+        let left = self.len.saturating_sub(current.len() + 1);
+        for syllable in &self.steps[start] {
+            let next = start + syllable.len();
+            if current.len() < self.len && self.fewest[next] <= left && left <= self.most[next] {
+                current.push(syllable.to_string());
+                self.collect(next, current, result);
+                current.pop();
+            }
+        }
+    }
+}
+
+/// Part of a word after the furthest index any split can reach, that is where splitting fails
+fn unsplittable_part(word: &str, syllables: &[&String]) -> String {
+    let mut reachable = vec![false; word.len() + 1];
+    reachable[0] = true;
+    let mut furthest = 0;
+
+    for i in 0..word.len() {
+        if !reachable[i] {
+            continue;
+        }
+        furthest = i;
+        for syllable in syllables {
+            if word[i..].starts_with(syllable.as_str()) {
+                reachable[i + syllable.len()] = true;
+            }
         }
     }
 
-    Ok(syl_res)
+    word[furthest..].to_string()
 }
 
 /// Validates if a chosen syllable is valid at a specified position with a select length
 pub fn is_syllable_pos_valid(syllable: &str, pos: usize, len: usize, cfg: &dyn LangConfig) -> bool {
     assert!(
-        pos <= len,
+        pos < len,
         "Position of syllable can't be greater than word length"
     );
 
-    let last_index = len - 1; // Index of last char
+    let last_index = len - 1; // Index of last syllable
     if let Some(position) = cfg.syllable_pos().get(syllable) {
-        match (position, last_index) {
-            (SyllablePosition::Start, 0) => true,
-            (SyllablePosition::Start, _) => false,
-            (SyllablePosition::NotStart, 0) => false,
-            (SyllablePosition::NotStart, _) => true,
-            (SyllablePosition::Middle, 0) => false,
-            (SyllablePosition::Middle, pos) => pos < last_index, // Is not last
-            (SyllablePosition::NotMiddle, 0) => true,            // Is first
-            (SyllablePosition::NotMiddle, pos) => pos == last_index, // Is last
-            (SyllablePosition::End, pos) => pos == last_index,   // Is last
-            (SyllablePosition::NotEnd, pos) => pos != last_index, // Is not last
-            (SyllablePosition::Any, _) => true,
+        match position {
+            SyllablePosition::Start => pos == 0,
+            SyllablePosition::NotStart => pos != 0,
+            SyllablePosition::Middle => pos != 0 && pos != last_index,
+            SyllablePosition::NotMiddle => pos == 0 || pos == last_index,
+            SyllablePosition::End => pos == last_index,
+            SyllablePosition::NotEnd => pos != last_index,
+            SyllablePosition::Any => true,
         }
     } else {
         false
     }
 }
 
-//TODO:lukx this function is useful but the user can't call it
-///// Validates every syllable in a single words if it is valid. Empty string
-///// or whitespace will return an error
-//pub fn is_word_valid(word: &str, cfg: &dyn LangConfig) -> Result<()> {
-//    if word.trim() == "" { return Err(InvalidSyllable("".to_string()));} // Empty string
-//
-//    let syllables = split_into_syllables(word,cfg)?;
-//
-//    for (pos,syllable) in syllables.iter().enumerate() {
-//        if !is_syllable_pos_valid(syllable,pos,syllables.len(),cfg) {
-//            return Err(InvalidSyllable(syllable.to_string()));
-//        }
-//    }
-//
-//    Ok(())
-//}
+/// Validates a syllable which would follow @previous syllables of a word with a select length.
+/// Checks its position and whether adjacency rules allow it after the last previous syllable
+pub fn is_syllable_valid_after(
+    syllable: &str,
+    previous: &[String],
+    len: usize,
+    cfg: &dyn LangConfig,
+) -> bool {
+    if !is_syllable_pos_valid(syllable, previous.len(), len, cfg) {
+        return false;
+    }
+
+    match previous.last() {
+        Some(last) => {
+            phonotactics::may_follow(last, syllable, cfg.adjacency_rules(), cfg.phonology())
+        }
+        None => true, // First syllable has nothing to follow
+    }
+}
+
+/// Upper bound of how many different words with @min_len to @max_len syllables can be
+/// made. Every valid sequence of syllables is counted, so a word which can be split in
+/// more ways is counted more times. Saturates instead of overflowing
+pub fn count_words(min_len: usize, max_len: usize, cfg: &dyn LangConfig) -> u128 {
+    let syllables: Vec<&String> = cfg.syllables().iter().filter(|s| !s.is_empty()).collect();
+
+    // followers[i] are indexes of syllables which may follow syllable i
+    let followers: Vec<Vec<usize>> = syllables
+        .iter()
+        .map(|prev| {
+            (0..syllables.len())
+                .filter(|&next| {
+                    phonotactics::may_follow(
+                        prev,
+                        syllables[next],
+                        cfg.adjacency_rules(),
+                        cfg.phonology(),
+                    )
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut total: u128 = 0;
+
+    for len in min_len.max(1)..=max_len {
+        // counts[i] is the amount of valid beginnings ending with syllable i
+        let mut counts: Vec<u128> = syllables
+            .iter()
+            .map(|s| is_syllable_pos_valid(s, 0, len, cfg) as u128)
+            .collect();
+
+        for pos in 1..len {
+            let mut next_counts = vec![0u128; syllables.len()];
+            for (prev, count) in counts.iter().enumerate().filter(|(_, c)| **c > 0) {
+                for &next in &followers[prev] {
+                    next_counts[next] = next_counts[next].saturating_add(*count);
+                }
+            }
+            for (i, count) in next_counts.iter_mut().enumerate() {
+                if !is_syllable_pos_valid(syllables[i], pos, len, cfg) {
+                    *count = 0;
+                }
+            }
+            counts = next_counts;
+        }
+
+        total = counts.iter().fold(total, |sum, c| sum.saturating_add(*c));
+    }
+
+    total
+}
+
+/// How many syllables have to be inserted, deleted or replaced to turn @a into @b
+pub fn edit_distance(a: &[String], b: &[String]) -> usize {
+    // previous[j] is the distance between the first i - 1 syllables of a and j of b
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, sa) in a.iter().enumerate() {
+        let mut current = Vec::with_capacity(b.len() + 1);
+        current.push(i + 1);
+
+        for (j, sb) in b.iter().enumerate() {
+            let replace = previous[j] + (sa != sb) as usize;
+            let delete = previous[j + 1] + 1;
+            let insert = current[j] + 1;
+            current.push(replace.min(delete).min(insert));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Validates every syllable in a single word, checking its position and whether it may follow
+/// the syllable before it. A word that can be split in more ways is valid if any of the splits
+/// is. Empty string or whitespace will return an error
+pub fn is_word_valid(word: &str, cfg: &dyn LangConfig) -> Result<()> {
+    if word.trim() == "" {
+        return Err(InvalidWord(word.to_string(), "word is empty".to_string()));
+    }
+
+    let steps = match split_steps(word, cfg) {
+        Ok(steps) => steps,
+        Err(InvalidSyllable(part)) => {
+            return Err(InvalidWord(
+                word.to_string(),
+                format!("{} can't be split into syllables", part),
+            ))
+        }
+        Err(e) => return Err(e),
+    };
+    let (fewest, most) = split_lengths(word, &steps);
+
+    for len in fewest[0]..=most[0] {
+        let mut dead_ends = HashSet::new();
+        if has_valid_split(
+            0,
+            &steps,
+            &fewest,
+            len,
+            &mut Vec::new(),
+            &mut dead_ends,
+            cfg,
+        ) {
+            return Ok(());
+        }
+    }
+
+    // Report the problem of the preferred split if no split is valid
+    let preferred = split_into_syllables(word, cfg)?;
+    Err(InvalidWord(
+        word.to_string(),
+        split_problem(&preferred, cfg).unwrap(), // No split is valid, so neither is this one
+    ))
+}
+
+/// Tells whether word[start..] can be split along @steps so that a word of @len syllables
+/// starting with @previous keeps position and adjacency rules. Only the count and the last
+/// of the previous syllables matter, so (start, count, last) found to fail are kept in
+/// @dead_ends and not walked again
+fn has_valid_split(
+    start: usize,
+    steps: &[Vec<&String>],
+    fewest: &[usize],
+    len: usize,
+    previous: &mut Vec<String>,
+    dead_ends: &mut HashSet<(usize, usize, Option<String>)>,
+    cfg: &dyn LangConfig,
+) -> bool {
+    if previous.len() == len || start == steps.len() - 1 {
+        return previous.len() == len && start == steps.len() - 1;
+    }
+    if fewest[start] > len - previous.len() {
+        return false; // Rest of the word needs more syllables than are left
+    }
+
+    let state = (start, previous.len(), previous.last().cloned());
+    if dead_ends.contains(&state) {
+        return false;
+    }
+
+    for syllable in &steps[start] {
+        if !is_syllable_valid_after(syllable, previous, len, cfg) {
+            continue;
+        }
+
+        previous.push(syllable.to_string());
+        let found = has_valid_split(
+            start + syllable.len(),
+            steps,
+            fewest,
+            len,
+            previous,
+            dead_ends,
+            cfg,
+        );
+        previous.pop();
+
+        if found {
+            return true;
+        }
+    }
+
+    dead_ends.insert(state);
+    false
+}
+
+/// Describes the first syllable of @split which breaks position or adjacency rules
+fn split_problem(split: &[String], cfg: &dyn LangConfig) -> Option<String> {
+    for (pos, syllable) in split.iter().enumerate() {
+        if !is_syllable_pos_valid(syllable, pos, split.len(), cfg) {
+            return Some(format!(
+                "syllable {} can't be at position {} of {}",
+                syllable,
+                pos + 1,
+                split.len()
+            ));
+        }
+        if !is_syllable_valid_after(syllable, &split[..pos], split.len(), cfg) {
+            return Some(format!(
+                "syllable {} can't follow {}",
+                syllable,
+                split[pos - 1] // Position is valid, so only adjacency can fail and pos > 0
+            ));
+        }
+    }
+
+    None
+}
+
 /// Replaces characters in word with their romanized equivalent.
 /// Returns error if word can't be split or a syllable in word can't be
 /// found in database
@@ -140,8 +502,11 @@ pub fn romanize(word: &str, cfg: &dyn LangConfig) -> Result<String> {
 pub fn syllables_sorted_by_occurrence(syllab: &HashMap<String, f64>) -> Vec<String> {
     let mut syllables: Vec<String> = syllab.keys().cloned().collect(); // Collect only keys (syllables)
 
-    syllables.sort_by(|a,b|  // Sort them by their position inside perc, desc
-        comp_f64(*syllab.get(b).unwrap(),*syllab.get(a).unwrap()));
+    // Sort them by their position inside perc, desc. Ties are sorted by name because
+    // HashMap order differs between runs
+    syllables.sort_by(|a, b| {
+        comp_f64(*syllab.get(b).unwrap(), *syllab.get(a).unwrap()).then_with(|| a.cmp(b))
+    });
     // Unwrap is safe because we just took them from perc HashMap
 
     syllables
@@ -169,8 +534,8 @@ pub fn db_syllable_occurrences_as_count(cfg: &dyn LangConfig) -> Result<HashMap<
 
     let mut count: HashMap<String, u32> = HashMap::new();
 
-    for word in cfg.database() {
-        let syllables = split_into_syllables(word, cfg)?;
+    for entry in cfg.database()? {
+        let syllables = split_into_syllables(&entry.word, cfg)?;
         for syllable in syllables {
             count.entry(syllable).and_modify(|e| *e += 1).or_insert(1); // Increment by 1 or if not found, set to 1
         }
@@ -188,6 +553,27 @@ pub fn db_syllable_occurrences_as_count(cfg: &dyn LangConfig) -> Result<HashMap<
     Ok(count)
 }
 
+/// Counts occurrences of syllables in the database separately at every position of words,
+/// indexed by WordPosition. Syllables which don't occur at a position have 0 there
+pub fn db_syllable_occurrences_by_position(
+    cfg: &dyn LangConfig,
+) -> Result<Vec<HashMap<String, u32>>> {
+    let empty: HashMap<String, u32> = cfg.syllables().iter().map(|s| (s.clone(), 0)).collect();
+    let mut counts = vec![empty; WordPosition::ALL.len()];
+
+    for entry in cfg.database()? {
+        let syllables = split_into_syllables(&entry.word, cfg)?;
+        let len = syllables.len();
+        for (pos, syllable) in syllables.into_iter().enumerate() {
+            *counts[WordPosition::of(pos, len) as usize]
+                .entry(syllable)
+                .or_insert(0) += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
 /// Converts count of syllables in into percentages. Will not work correctly if syllables with no
 /// occurrence but presence in DB are not in @count.
 pub fn db_syllable_occurrences_as_percentage(count: &HashMap<String, u32>) -> HashMap<String, f64> {
@@ -0,0 +1,242 @@
+use crate::config::LangConfig;
+use crate::error::LangErr::{self, ConfigChanged, ConfigParse, MissingFile, Serialization};
+use crate::filesafety::{self, FileStamp};
+use crate::filesystemconfig::read_file;
+use crate::languagepack::{LanguageInfo, LanguagePack};
+use crate::lexicon::LexiconEntry;
+use crate::lint::{self, LintIssue};
+use crate::phonology::Phonology;
+use crate::phonotactics::AdjacencyRule;
+use crate::syllables::SyllablePosition;
+use crate::tuning::Tuning;
+use crate::wanted::Wanted;
+use crate::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const LANGUAGE_FILE_NAME: &str = "language.toml";
+
+/// Language config stored in a single TOML file, see LanguagePack
+pub struct TomlConfig {
+    // DATA
+    syllables: Vec<String>,
+    syllable_pos: HashMap<String, SyllablePosition>,
+    utf8_to_ascii: HashMap<String, String>,
+    wanted: HashMap<String, Wanted>,
+    phonology: Phonology,
+    adjacency_rules: Vec<AdjacencyRule>,
+    tuning: Tuning,
+    database: Vec<LexiconEntry>,
+    // Not used by the config itself, kept so it is written back and exported
+    info: LanguageInfo,
+    // Stamp of the file when it was loaded, None if it was not
+    loaded_stamp: Option<Option<FileStamp>>,
+    // FILE PATHS
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl TomlConfig {
+    pub fn in_dir(dir: &Path) -> TomlConfig {
+        TomlConfig {
+            syllables: Vec::new(),
+            syllable_pos: HashMap::new(),
+            utf8_to_ascii: HashMap::new(),
+            wanted: HashMap::new(),
+            phonology: Phonology::default(),
+            adjacency_rules: Vec::new(),
+            tuning: Tuning::default(),
+            database: Vec::new(),
+            info: LanguageInfo {
+                name: dir.file_name().map(|n| n.to_string_lossy().to_string()),
+                description: None,
+            },
+            loaded_stamp: None,
+            dir: dir.to_path_buf(),
+            path: dir.join(LANGUAGE_FILE_NAME),
+        }
+    }
+
+    fn read_pack(&self) -> Result<LanguagePack> {
+        let content = read_file(&self.path)?;
+
+        // Lines are only known for syntax errors, parsed values don't remember where they were
+        let value: toml::Value = toml::from_str(&content).map_err(|e| parse_err(&e))?;
+        value.try_into().map_err(|e| parse_err(&e))
+    }
+}
+
+fn parse_err(e: &toml::de::Error) -> LangErr {
+    // Position is reported as a line of its own
+    let mut message = e.to_string();
+    if let Some(i) = message.find(" at line ") {
+        message.truncate(i);
+    }
+
+    ConfigParse {
+        file: LANGUAGE_FILE_NAME.to_string(),
+        line: e.line_col().map(|(line, _)| line + 1),
+        message,
+    }
+}
+
+impl LangConfig for TomlConfig {
+    fn syllables(&self) -> &Vec<String> {
+        &self.syllables
+    }
+
+    fn set_syllables(&mut self, syllables: Vec<String>) {
+        self.syllables = syllables;
+    }
+
+    fn syllable_pos(&self) -> &HashMap<String, SyllablePosition> {
+        &self.syllable_pos
+    }
+
+    fn set_syllable_pos(&mut self, syllable_pos: HashMap<String, SyllablePosition>) {
+        self.syllable_pos = syllable_pos;
+    }
+
+    fn romanization(&self) -> &HashMap<String, String> {
+        &self.utf8_to_ascii
+    }
+
+    fn set_romanization(&mut self, utf_to_ascii: HashMap<String, String>) {
+        self.utf8_to_ascii = utf_to_ascii;
+    }
+
+    fn wanted(&self) -> &HashMap<String, Wanted> {
+        &self.wanted
+    }
+
+    fn set_wanted(&mut self, wanted: HashMap<String, Wanted>) {
+        self.wanted = wanted;
+    }
+
+    fn phonology(&self) -> &Phonology {
+        &self.phonology
+    }
+
+    fn set_phonology(&mut self, phonology: Phonology) {
+        self.phonology = phonology;
+    }
+
+    fn adjacency_rules(&self) -> &Vec<AdjacencyRule> {
+        &self.adjacency_rules
+    }
+
+    fn set_adjacency_rules(&mut self, rules: Vec<AdjacencyRule>) {
+        self.adjacency_rules = rules;
+    }
+
+    fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+    }
+
+    fn database(&self) -> Result<&Vec<LexiconEntry>> {
+        Ok(&self.database)
+    }
+
+    fn set_database(&mut self, db: Vec<LexiconEntry>) {
+        self.database = db;
+    }
+
+    fn append_database(&mut self, entries: &[LexiconEntry]) {
+        self.database.extend_from_slice(entries);
+    }
+
+    fn delete_from_database(&mut self, word: &str) -> Result<Option<LexiconEntry>> {
+        match self.database.iter().position(|e| e.word == word) {
+            Some(i) => Ok(Some(self.database.remove(i))),
+            None => Ok(None),
+        }
+    }
+
+    fn delete_entry_from_database(&mut self, entry: &LexiconEntry) -> Result<bool> {
+        match self.database.iter().position(|e| e == entry) {
+            Some(i) => {
+                self.database.remove(i);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn info(&self) -> Option<&LanguageInfo> {
+        Some(&self.info)
+    }
+
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn load(&mut self) -> Result<()> {
+        self.loaded_stamp = Some(filesafety::stamp(&self.path));
+        let mut pack = self.read_pack()?;
+        pack.tuning.check().map_err(|message| ConfigParse {
+            file: LANGUAGE_FILE_NAME.to_string(),
+            line: None,
+            message,
+        })?;
+
+        if pack.language.name.is_some() {
+            self.info = pack.language.clone();
+        }
+        pack.language = LanguageInfo::default();
+        pack.apply_to(self);
+
+        Ok(())
+    }
+
+    fn remove(&mut self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+
+        Ok(())
+    }
+
+    fn lint(&self) -> Result<Vec<LintIssue>> {
+        match self.read_pack() {
+            Ok(pack) => Ok(lint::lint_pack(LANGUAGE_FILE_NAME, &pack)),
+            Err(ConfigParse {
+                file,
+                line,
+                message,
+            }) => Ok(vec![LintIssue::new(&file, line, message)]),
+            Err(MissingFile(_)) => Ok(vec![LintIssue::new(
+                LANGUAGE_FILE_NAME,
+                None,
+                "file does not exist".to_string(),
+            )]),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let mut pack = LanguagePack::from_config(self)?;
+        pack.language = self.info.clone();
+
+        let content = toml::to_string(&pack).map_err(|e| Serialization(e.to_string()))?;
+
+        if let Some(loaded) = self.loaded_stamp {
+            if filesafety::stamp(&self.path) != loaded {
+                return Err(ConfigChanged(self.path.display().to_string()));
+            }
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        filesafety::write_atomically(&self.path, content.as_bytes())?;
+
+        if self.loaded_stamp.is_some() {
+            self.loaded_stamp = Some(filesafety::stamp(&self.path));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Names of metrics the off by score of CalculatedRandom can be measured with
+pub const METRIC_NAMES: [&str; 3] = ["l1", "l2", "kl"];
+
+/// Keys tuning values are stored under
+pub const TUNING_KEYS: [&str; 3] = ["top_fraction", "metric", "temperature"];
+
+/// (Argument name, key) of values which can be given on the command line
+pub const TUNING_ARGS: [(&str, &str); 3] = [
+    ("top-fraction", "top_fraction"),
+    ("metric", "metric"),
+    ("temperature", "temperature"),
+];
+
+/// How far real occurrence of syllables is from wanted occurrence
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    /// Sum of absolute differences
+    L1,
+    /// Sum of squared differences, a single syllable far off weighs more
+    L2,
+    /// Kullback-Leibler divergence of real occurrence from wanted occurrence
+    Kl,
+}
+
+impl Metric {
+    pub fn from_keyword(keyword: &str) -> Option<Metric> {
+        match keyword {
+            "l1" => Some(Metric::L1),
+            "l2" => Some(Metric::L2),
+            "kl" => Some(Metric::Kl),
+            _ => None,
+        }
+    }
+
+    /// Part of the distance contributed by a syllable which is @wanted and occurs as @real
+    pub fn term(self, wanted: f64, real: f64) -> f64 {
+        match self {
+            Metric::L1 => (wanted - real).abs(),
+            Metric::L2 => (wanted - real) * (wanted - real),
+            Metric::Kl if wanted > 0.0 => wanted * (wanted / real).ln(),
+            Metric::Kl => 0.0, // Syllables which are not wanted don't count
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self {
+            Metric::L1 => "l1",
+            Metric::L2 => "l2",
+            Metric::Kl => "kl",
+        };
+        write!(f, "{}", keyword)
+    }
+}
+
+/// Trade off of CalculatedRandom between matching wanted values and variety of words
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+    /// Share of the best scored syllables the pulled one is chosen from
+    pub top_fraction: f64,
+    pub metric: Metric,
+    /// Syllables are picked by softmax of their scores, lower is closer to the best one.
+    /// None picks every syllable of the top fraction equally often
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+}
+
+impl Default for Tuning {
+    fn default() -> Tuning {
+        Tuning {
+            top_fraction: 0.15,
+            metric: Metric::L1,
+            temperature: None,
+        }
+    }
+}
+
+impl Tuning {
+    pub fn is_default(&self) -> bool {
+        *self == Tuning::default()
+    }
+
+    /// Sets value under @key from its text form, error tells what is wrong with it
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+
+        match key {
+            "top_fraction" => {
+                self.top_fraction = value
+                    .parse()
+                    .map_err(|_| format!("expected number as top_fraction, got '{}'", value))?
+            }
+            "metric" => {
+                self.metric = Metric::from_keyword(value).ok_or_else(|| {
+                    format!(
+                        "unknown metric '{}', expected one of {}",
+                        value,
+                        METRIC_NAMES.join(", ")
+                    )
+                })?
+            }
+            "temperature" if value == "off" => self.temperature = None,
+            "temperature" => {
+                self.temperature = Some(value.parse().map_err(|_| {
+                    format!("expected number or 'off' as temperature, got '{}'", value)
+                })?)
+            }
+            _ => {
+                return Err(format!(
+                    "unknown tuning key '{}', expected one of {}",
+                    key,
+                    TUNING_KEYS.join(", ")
+                ))
+            }
+        }
+
+        self.check()
+    }
+
+    /// Error tells which value is out of its range
+    pub fn check(&self) -> Result<(), String> {
+        if !(self.top_fraction > 0.0 && self.top_fraction <= 1.0) {
+            return Err(format!(
+                "top_fraction {} is not more than 0 and at most 1",
+                self.top_fraction
+            ));
+        }
+        match self.temperature {
+            Some(t) if !(t.is_finite() && t > 0.0) => {
+                Err(format!("temperature {} is not a number more than 0", t))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// (Key, value) of every value set, in the text form set takes
+    pub fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![
+            ("top_fraction", self.top_fraction.to_string()),
+            ("metric", self.metric.to_string()),
+        ];
+        if let Some(t) = self.temperature {
+            pairs.push(("temperature", t.to_string()));
+        }
+
+        pairs
+    }
+}
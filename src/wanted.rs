@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// Position of a syllable in a word which may have wanted values of its own.
+/// The only syllable of a word counts as its start
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum WordPosition {
+    Start = 0,
+    Middle = 1,
+    End = 2,
+}
+
+impl WordPosition {
+    pub const ALL: [WordPosition; 3] =
+        [WordPosition::Start, WordPosition::Middle, WordPosition::End];
+
+    /// Position @pos of a word with @len syllables
+    pub fn of(pos: usize, len: usize) -> WordPosition {
+        if pos == 0 {
+            WordPosition::Start
+        } else if pos == len - 1 {
+            WordPosition::End
+        } else {
+            WordPosition::Middle
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<WordPosition> {
+        match keyword {
+            "start" => Some(WordPosition::Start),
+            "middle" => Some(WordPosition::Middle),
+            "end" => Some(WordPosition::End),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WordPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self {
+            WordPosition::Start => "start",
+            WordPosition::Middle => "middle",
+            WordPosition::End => "end",
+        };
+        write!(f, "{}", keyword)
+    }
+}
+
+/// How often a syllable is wanted among all syllables and optionally among syllables
+/// at a single position of words
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Wanted {
+    pub overall: f64,
+    /// Indexed by WordPosition, None if the overall value is wanted there too
+    pub positional: [Option<f64>; 3],
+}
+
+impl Wanted {
+    /// Wanted the same everywhere
+    pub fn overall(overall: f64) -> Wanted {
+        Wanted {
+            overall,
+            positional: [None; 3],
+        }
+    }
+
+    /// Value wanted at @position, the overall one if there is none of its own
+    pub fn at(&self, position: WordPosition) -> f64 {
+        self.positional[position as usize].unwrap_or(self.overall)
+    }
+
+    /// Value set for @position only, None if it is not set
+    pub fn only_at(&self, position: WordPosition) -> Option<f64> {
+        self.positional[position as usize]
+    }
+
+    pub fn set_at(&mut self, position: WordPosition, value: Option<f64>) {
+        self.positional[position as usize] = value;
+    }
+
+    /// Parses the value part of a line of the wanted file, e.g. `0.05 start=0.1 end=0.02`.
+    /// Error tells what is wrong with it
+    pub fn from_value(value: &str) -> Result<Wanted, String> {
+        let mut parts = value.split_whitespace();
+        let mut wanted = Wanted::overall(parse_share(parts.next().unwrap_or_default())?);
+
+        for part in parts {
+            let mut split = part.splitn(2, '=');
+            let keyword = split.next().unwrap_or_default();
+            let position = WordPosition::from_keyword(keyword)
+                .ok_or_else(|| format!("expected start=, middle= or end= instead of '{}'", part))?;
+
+            if wanted.only_at(position).is_some() {
+                return Err(format!("{} is set more than once", position));
+            }
+            wanted.set_at(
+                position,
+                Some(parse_share(split.next().unwrap_or_default())?),
+            );
+        }
+
+        Ok(wanted)
+    }
+}
+
+impl fmt::Display for Wanted {
+    /// Same form as is used in the wanted file
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.overall)?;
+        for position in WordPosition::ALL.iter() {
+            if let Some(value) = self.only_at(*position) {
+                write!(f, " {}={}", position, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a single wanted number, error tells what is wrong with it
+fn parse_share(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(wanted) if wanted.is_finite() && wanted >= 0.0 => Ok(wanted),
+        Ok(_) => Err("expected a number which is not negative".to_string()),
+        Err(_) => Err(format!("expected number instead of '{}'", value)),
+    }
+}
@@ -0,0 +1,230 @@
+use crate::config::LangConfig;
+use crate::rangen::{EngineRng, RandomEngine};
+use crate::wanted::WordPosition;
+use crate::{rangen, syllables, Result};
+use rand::Rng;
+
+/// Pulls tried before the valid syllables are listed and picked from instead
+const MAX_TRIES: u32 = 100;
+
+/// Pulls syllables randomly, each as often as it is wanted at its position. Positions are
+/// respected by keeping a table for every kind of position, adjacency rules by pulling again
+pub struct WeightedRandom {
+    // Indexed by PositionClass
+    tables: Vec<Option<AliasTable>>,
+    rng: EngineRng,
+}
+
+/// Kinds of positions in a word, syllables valid at one position of a kind are valid at all
+#[derive(Clone, Copy)]
+enum PositionClass {
+    /// The only syllable of the word
+    Only = 0,
+    Initial = 1,
+    Medial = 2,
+    Final = 3,
+}
+
+impl PositionClass {
+    const ALL: [PositionClass; 4] = [
+        PositionClass::Only,
+        PositionClass::Initial,
+        PositionClass::Medial,
+        PositionClass::Final,
+    ];
+
+    fn of(pos: usize, len: usize) -> PositionClass {
+        if len == 1 {
+            PositionClass::Only
+        } else if pos == 0 {
+            PositionClass::Initial
+        } else if pos == len - 1 {
+            PositionClass::Final
+        } else {
+            PositionClass::Medial
+        }
+    }
+
+    /// (Position, length) of a word this kind of position is found in
+    fn example(self) -> (usize, usize) {
+        match self {
+            PositionClass::Only => (0, 1),
+            PositionClass::Initial => (0, 3),
+            PositionClass::Medial => (1, 3),
+            PositionClass::Final => (2, 3),
+        }
+    }
+}
+
+/// Value @syllable is wanted at positions of @class, 0 if it is not wanted at all
+fn wanted_at(syllable: &str, class: PositionClass, cfg: &dyn LangConfig) -> f64 {
+    let (pos, len) = class.example();
+
+    match cfg.wanted().get(syllable) {
+        Some(wanted) => wanted.at(WordPosition::of(pos, len)),
+        None => 0.0,
+    }
+}
+
+/// Vose's alias table, pulls an item in constant time with a chance proportional to its weight
+struct AliasTable {
+    items: Vec<usize>,
+    // Chance of keeping the item of a column instead of taking its alias
+    keep: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Table of (item, weight) pairs, None if no item weighs anything
+    fn new(weighted: &[(usize, f64)]) -> Option<AliasTable> {
+        let weighted: Vec<&(usize, f64)> = weighted.iter().filter(|(_, w)| *w > 0.0).collect();
+        let total: f64 = weighted.iter().map(|(_, w)| w).sum();
+        if weighted.is_empty() || !total.is_finite() {
+            return None;
+        }
+
+        let n = weighted.len();
+        // Weights scaled so that an average column is exactly full
+        let mut scaled: Vec<f64> = weighted.iter().map(|(_, w)| w * n as f64 / total).collect();
+        let mut keep = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            // Column s is filled up by l
+            keep[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Columns left over are full, up to rounding
+
+        Some(AliasTable {
+            items: weighted.iter().map(|(item, _)| *item).collect(),
+            keep,
+            alias,
+        })
+    }
+
+    fn pull(&self, rng: &mut EngineRng) -> usize {
+        let column = rangen::random_index(rng, self.items.len());
+
+        if rng.gen::<f64>() < self.keep[column] {
+            self.items[column]
+        } else {
+            self.items[self.alias[column]]
+        }
+    }
+}
+
+impl WeightedRandom {
+    fn pull_syllable(
+        &mut self,
+        previous: &[String],
+        len: usize,
+        cfg: &dyn LangConfig,
+    ) -> Result<String> {
+        let pos = previous.len();
+        let table = match &self.tables[PositionClass::of(pos, len) as usize] {
+            Some(table) => table,
+            // No syllable is wanted here, none was tried
+            None => return Err(rangen::no_valid_syllable(pos, len, Vec::new())),
+        };
+
+        for _try in 0..MAX_TRIES {
+            let syllable = &cfg.syllables()[table.pull(&mut self.rng)];
+
+            // Position is valid already, only adjacency rules may reject it
+            if syllables::is_syllable_valid_after(syllable, previous, len, cfg) {
+                return Ok(syllable.to_string());
+            }
+        }
+
+        // Valid syllables are rare here, pick from all of them instead of guessing
+        let valid: Vec<(&String, f64)> = cfg
+            .syllables()
+            .iter()
+            .filter(|s| syllables::is_syllable_valid_after(s, previous, len, cfg))
+            .map(|s| (s, wanted_at(s, PositionClass::of(pos, len), cfg)))
+            .filter(|(_, w)| *w > 0.0)
+            .collect();
+
+        let mut pick = self.rng.gen::<f64>() * valid.iter().map(|(_, w)| w).sum::<f64>();
+        for (syllable, weight) in &valid {
+            if pick < *weight {
+                return Ok(syllable.to_string());
+            }
+            pick -= weight;
+        }
+
+        match valid.last() {
+            Some((syllable, _)) => Ok(syllable.to_string()), // Rounding left a bit of pick
+            None => Err(rangen::no_valid_syllable(
+                pos,
+                len,
+                rangen::invalid_syllables(previous, len, cfg),
+            )),
+        }
+    }
+
+    fn create_word(&mut self, len: usize, cfg: &dyn LangConfig) -> Result<String> {
+        let mut syllables = Vec::with_capacity(len);
+
+        for _pos in 0..len {
+            let syllable = self.pull_syllable(&syllables, len, cfg)?;
+            syllables.push(syllable);
+        }
+
+        Ok(syllables.concat())
+    }
+}
+
+impl RandomEngine for WeightedRandom {
+    fn create_words(
+        &mut self,
+        min_len: u32,
+        max_len: u32,
+        count: u32,
+        cfg: &dyn LangConfig,
+    ) -> Result<Vec<String>> {
+        let mut result = Vec::with_capacity(count as usize);
+
+        for _nth_word in 0..count {
+            let chosen_length = self.rng.gen_range(min_len, max_len + 1) as usize;
+            let word = rangen::retry_word(|| self.create_word(chosen_length, cfg))?;
+
+            result.push(word);
+        }
+
+        Ok(result)
+    }
+
+    fn with_config(cfg: &dyn LangConfig, seed: Option<u64>) -> Result<WeightedRandom> {
+        let tables = PositionClass::ALL
+            .iter()
+            .map(|class| {
+                let (pos, len) = class.example();
+                let weighted: Vec<(usize, f64)> = cfg
+                    .syllables()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| !s.is_empty())
+                    .filter(|(_, s)| syllables::is_syllable_pos_valid(s, pos, len, cfg))
+                    .map(|(i, s)| (i, wanted_at(s, *class, cfg)))
+                    .collect();
+
+                AliasTable::new(&weighted)
+            })
+            .collect();
+
+        Ok(WeightedRandom {
+            tables,
+            rng: rangen::engine_rng(seed),
+        })
+    }
+}